@@ -1,10 +1,60 @@
-use day_01::part1::process;
-use miette::Context;
+use std::{fs::File, io::BufReader};
+
+use day_01::{
+    explain::{explain, Part},
+    part1::{decode_line_with, process_with, DigitClass},
+    stream::process_reader_par,
+};
+use miette::{Context, IntoDiagnostic};
+
+// Lines decoded in parallel at once when streaming a document given on the command line
+const CHUNK_LINES: usize = 4096;
 
 fn main() -> miette::Result<()> {
-    let file = include_str!("../../input1.txt");
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let explain_lines = args.iter().any(|arg| arg == "--explain");
+
+    // `--digits <ascii|unicode|reject>` selects which characters are taken as digits
+    let digits = args.iter().position(|arg| arg == "--digits");
+    let class = match digits.and_then(|index| args.get(index + 1)) {
+        Some(class) => class.parse::<DigitClass>()?,
+        None => DigitClass::default(),
+    };
+    let path = args
+        .iter()
+        .enumerate()
+        .find(|(index, arg)| !arg.starts_with("--") && digits.map(|d| d + 1) != Some(*index))
+        .map(|(_, arg)| arg);
+
+    // Explaining needs the whole document, so a path given as argument is loaded in that case
+    if explain_lines {
+        let file = match path {
+            Some(path) => std::fs::read_to_string(path)
+                .into_diagnostic()
+                .with_context(|| format!("read {path}"))?,
+            None => include_str!("../../input1.txt").to_string(),
+        };
+        for line in explain(&file, Part::One(class)) {
+            println!("{}", line);
+        }
+    }
 
-    let result = process(file).context("process part 1")?;
+    // A path given as argument is streamed instead of loading the bundled input
+    let result = match path {
+        Some(path) => {
+            let file = File::open(path)
+                .into_diagnostic()
+                .with_context(|| format!("open {path}"))?;
+            process_reader_par(BufReader::new(file), CHUNK_LINES, |number, line| {
+                decode_line_with(number, line, class)
+            })
+            .context("process part 1")?
+        }
+        None => {
+            let file = include_str!("../../input1.txt");
+            process_with(file, class).context("process part 1")?
+        }
+    };
     println!("{}", result);
 
     Ok(())
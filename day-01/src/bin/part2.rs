@@ -1,10 +1,73 @@
-use day_01::part2::process;
-use miette::Context;
+use std::{fs::File, io::BufReader};
+
+use day_01::{
+    explain::{explain, Part},
+    part2::{decode_line_with, process_with, Decoding, OverlapPolicy, Vocabulary},
+    stream::process_reader_par,
+};
+use miette::{Context, IntoDiagnostic};
+
+// Lines decoded in parallel at once when streaming a document given on the command line
+const CHUNK_LINES: usize = 4096;
 
 fn main() -> miette::Result<()> {
-    let file = include_str!("../../input1.txt");
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let explain_lines = args.iter().any(|arg| arg == "--explain");
+
+    // `--overlap <allow|longest|first>` selects how spelled digits sharing letters are decoded
+    // and `--vocabulary <digits|extended>` which spelled numbers are recognised
+    let value_of = |name: &str| {
+        let index = args.iter().position(|arg| arg == name)?;
+        args.get(index + 1)
+    };
+    let decoding = Decoding {
+        vocabulary: match value_of("--vocabulary") {
+            Some(vocabulary) => vocabulary.parse::<Vocabulary>()?,
+            None => Vocabulary::default(),
+        },
+        overlap: match value_of("--overlap") {
+            Some(policy) => policy.parse::<OverlapPolicy>()?,
+            None => OverlapPolicy::default(),
+        },
+    };
+    let path = args
+        .iter()
+        .enumerate()
+        .find(|(index, arg)| {
+            let is_value = *index > 0 && ["--overlap", "--vocabulary"].contains(&&*args[index - 1]);
+            !arg.starts_with("--") && !is_value
+        })
+        .map(|(_, arg)| arg);
+
+    // Explaining needs the whole document, so a path given as argument is loaded in that case
+    if explain_lines {
+        let file = match path {
+            Some(path) => std::fs::read_to_string(path)
+                .into_diagnostic()
+                .with_context(|| format!("read {path}"))?,
+            None => include_str!("../../input1.txt").to_string(),
+        };
+        for line in explain(&file, Part::Two(decoding)) {
+            println!("{}", line);
+        }
+    }
 
-    let result = process(file).context("process part 2")?;
+    // A path given as argument is streamed instead of loading the bundled input
+    let result = match path {
+        Some(path) => {
+            let file = File::open(path)
+                .into_diagnostic()
+                .with_context(|| format!("open {path}"))?;
+            process_reader_par(BufReader::new(file), CHUNK_LINES, |number, line| {
+                decode_line_with(number, line, decoding)
+            })
+            .context("process part 2")?
+        }
+        None => {
+            let file = include_str!("../../input1.txt");
+            process_with(file, decoding).context("process part 2")?
+        }
+    };
     println!("{}", result);
 
     Ok(())
@@ -1,4 +1,4 @@
-use miette::Diagnostic;
+use miette::{Diagnostic, SourceSpan};
 use thiserror::Error;
 
 #[derive(Error, Diagnostic, Debug)]
@@ -6,4 +6,43 @@ pub enum AocError {
     #[error(transparent)]
     #[diagnostic(code(aoc::io_error))]
     IoError(#[from] std::io::Error),
+
+    #[error("line {line} holds no digit to calibrate from")]
+    #[diagnostic(code(aoc::no_digit))]
+    NoDigit { line: usize },
+
+    #[error("unknown overlap policy `{0}`")]
+    #[diagnostic(
+        code(aoc::unknown_overlap_policy),
+        help("expected one of `allow`, `longest` or `first`")
+    )]
+    UnknownOverlapPolicy(String),
+
+    #[error("unknown vocabulary `{0}`")]
+    #[diagnostic(
+        code(aoc::unknown_vocabulary),
+        help("expected one of `digits` or `extended`")
+    )]
+    UnknownVocabulary(String),
+
+    #[error("line {line} holds the unexpected numeric character `{character}`")]
+    #[diagnostic(
+        code(aoc::unexpected_numeric),
+        help("only the digits of the selected digit class are accepted")
+    )]
+    UnexpectedNumeric {
+        line: usize,
+        character: char,
+        #[source_code]
+        src: String,
+        #[label("not a digit of the selected class")]
+        span: SourceSpan,
+    },
+
+    #[error("unknown digit class `{0}`")]
+    #[diagnostic(
+        code(aoc::unknown_digit_class),
+        help("expected one of `ascii`, `unicode` or `reject`")
+    )]
+    UnknownDigitClass(String),
 }
@@ -0,0 +1,99 @@
+use std::cmp::Reverse;
+
+// Tells whether a digit was written as a numeral or spelled out as a word
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigitSource {
+    Numeral,
+    Word,
+}
+
+// A number found in a line of the document, `start..end` being its byte range in the line. Its
+// value is a single digit unless it was spelled out with an extended vocabulary, like
+// `twentythree`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigitMatch {
+    pub start: usize,
+    pub end: usize,
+    pub value: i64,
+    pub source: DigitSource,
+}
+
+impl DigitMatch {
+    // Gives back the first digit the value is written with, e.g. 2 for 23
+    pub fn leading_digit(&self) -> i64 {
+        let mut value = self.value;
+        while value >= 10 {
+            value /= 10;
+        }
+        value
+    }
+
+    // Gives back the last digit the value is written with, e.g. 3 for 23
+    pub fn trailing_digit(&self) -> i64 {
+        self.value % 10
+    }
+
+    // Gives back the text the digit was decoded from
+    pub fn text<'a>(&self, line: &'a str) -> &'a str {
+        &line[self.start..self.end]
+    }
+}
+
+// Gives back the match a calibration value takes its last digit from, the one ending last. The
+// longest one is preferred among those ending at the same position, so that an overlapping
+// `sixty` wins over the `six` it starts with.
+pub fn last_match(matches: &[DigitMatch]) -> Option<&DigitMatch> {
+    matches
+        .iter()
+        .max_by_key(|digit| (digit.end, Reverse(digit.start)))
+}
+
+// First code point of each run of ten decimal digits (Unicode category Nd) outside of ASCII, as
+// of Unicode 17.0, the version `char::is_numeric` follows. Generated with the Python `regex`
+// module, whose Unicode data is that version too:
+//     nd = [c for c in range(0x80, 0x110000) if regex.match(r"\p{Nd}", chr(c))]
+//     nd[::10]
+// It must be regenerated whenever the toolchain moves to a newer Unicode version, or the new
+// digits would be rejected as numeric characters that are not digits.
+pub const DECIMAL_ZEROS_UNICODE_VERSION: (u8, u8, u8) = (17, 0, 0);
+const DECIMAL_ZEROS: [u32; 76] = [
+    0x0660, 0x06F0, 0x07C0, 0x0966, 0x09E6, 0x0A66, 0x0AE6, 0x0B66, 0x0BE6, 0x0C66, 0x0CE6, 0x0D66,
+    0x0DE6, 0x0E50, 0x0ED0, 0x0F20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0x19D0, 0x1A80, 0x1A90,
+    0x1B50, 0x1BB0, 0x1C40, 0x1C50, 0xA620, 0xA8D0, 0xA900, 0xA9D0, 0xA9F0, 0xAA50, 0xABF0, 0xFF10,
+    0x104A0, 0x10D30, 0x10D40, 0x11066, 0x110F0, 0x11136, 0x111D0, 0x112F0, 0x11450, 0x114D0,
+    0x11650, 0x116C0, 0x116D0, 0x116DA, 0x11730, 0x118E0, 0x11950, 0x11BF0, 0x11C50, 0x11D50,
+    0x11DA0, 0x11DE0, 0x11F50, 0x16130, 0x16A60, 0x16AC0, 0x16B50, 0x16D70, 0x1CCF0, 0x1D7CE,
+    0x1D7D8, 0x1D7E2, 0x1D7EC, 0x1D7F6, 0x1E140, 0x1E2F0, 0x1E4F0, 0x1E5F1, 0x1E950, 0x1FBF0,
+];
+
+// Gives back the value of a decimal digit of any script, e.g. 3 for `3`, `٣` or `३`
+pub fn decimal_value(character: char) -> Option<u32> {
+    if let Some(value) = character.to_digit(10) {
+        return Some(value);
+    }
+    let code = character as u32;
+    DECIMAL_ZEROS
+        .iter()
+        .find(|&&zero| (zero..zero + 10).contains(&code))
+        .map(|zero| code - zero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_zeros_version() {
+        assert_eq!(
+            char::UNICODE_VERSION,
+            DECIMAL_ZEROS_UNICODE_VERSION,
+            "DECIMAL_ZEROS must be regenerated for the Unicode version of the toolchain"
+        );
+        for zero in DECIMAL_ZEROS {
+            for code in zero..zero + 10 {
+                let character = char::from_u32(code).unwrap();
+                assert!(character.is_numeric(), "{character:?}");
+            }
+        }
+    }
+}
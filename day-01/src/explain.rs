@@ -0,0 +1,225 @@
+use std::fmt;
+
+use crate::{
+    digit::{last_match, DigitMatch, DigitSource},
+    part1::{self, DigitClass},
+    part2::{self, Decoding},
+    stream::document_lines,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One(DigitClass),
+    Two(Decoding),
+}
+
+// How a single line of the document was decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineExplanation<'a> {
+    pub line_number: usize,
+    pub line: &'a str,
+    pub first: Option<DigitMatch>,
+    pub last: Option<DigitMatch>,
+    // The numeric characters the digit class rejects, along with their byte offsets
+    pub rejected: Vec<(usize, char)>,
+}
+
+impl LineExplanation<'_> {
+    // Gives back the calibration value of the line, if it holds any digit and no rejected
+    // character
+    pub fn value(&self) -> Option<i64> {
+        if !self.rejected.is_empty() {
+            return None;
+        }
+        Some(10 * self.first?.leading_digit() + self.last?.trailing_digit())
+    }
+}
+
+// Draws carets under the characters of the line whose byte offset is selected, aligned on
+// characters rather than bytes
+fn carets(line: &str, selected: impl Fn(usize) -> bool) -> String {
+    line.char_indices()
+        .map(|(index, _)| if selected(index) { '^' } else { ' ' })
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+fn describe(line: &str, digit: &DigitMatch) -> String {
+    let source = match digit.source {
+        DigitSource::Numeral => "numeral",
+        DigitSource::Word => "word",
+    };
+    format!(
+        "{source} \"{}\" at {}..{}",
+        digit.text(line),
+        digit.start,
+        digit.end
+    )
+}
+
+// Prints the line with carets under the first and last digits, e.g.
+//     4 | xtwone3four => 24
+//       |  ^^^   ^^^^ first: word "two" at 1..4, last: word "four" at 7..11
+// or under the characters the digit class rejects
+impl fmt::Display for LineExplanation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = self.line.trim_end();
+        if !self.rejected.is_empty() {
+            let characters = self
+                .rejected
+                .iter()
+                .map(|(_, character)| format!("`{character}`"))
+                .collect::<Vec<String>>();
+            writeln!(
+                f,
+                "{:>5} | {line} => rejected {}",
+                self.line_number,
+                characters.join(", ")
+            )?;
+            return write!(
+                f,
+                "      | {} not digits of the selected class",
+                carets(line, |index| self
+                    .rejected
+                    .iter()
+                    .any(|(offset, _)| *offset == index))
+            );
+        }
+
+        let (first, last, value) = match (self.first, self.last, self.value()) {
+            (Some(first), Some(last), Some(value)) => (first, last, value),
+            _ => return write!(f, "{:>5} | {line} => no digit", self.line_number),
+        };
+
+        writeln!(f, "{:>5} | {line} => {value}", self.line_number)?;
+
+        write!(
+            f,
+            "      | {} first: {}, last: {}",
+            carets(line, |index| [first, last]
+                .iter()
+                .any(|digit| (digit.start..digit.end).contains(&index))),
+            describe(line, &first),
+            describe(line, &last)
+        )
+    }
+}
+
+// Explains, line by line, which digits the calibration value is computed from. Lines are split
+// and numbered the way `process` does, so that they match the lines its errors report.
+pub fn explain(input: &str, part: Part) -> Vec<LineExplanation<'_>> {
+    document_lines(input)
+        .map(|(line_number, line)| {
+            let (digits, rejected) = match part {
+                Part::One(class) => (
+                    part1::digit_matches_with(line, class),
+                    part1::rejected_characters(line, class),
+                ),
+                Part::Two(decoding) => (part2::digit_matches_with(line, decoding), vec![]),
+            };
+            LineExplanation {
+                line_number,
+                line,
+                first: digits.first().copied(),
+                last: last_match(&digits).copied(),
+                rejected,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain() {
+        let input = "two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen";
+        let explanations = explain(input, Part::Two(Decoding::default()));
+
+        assert_eq!(
+            vec![29, 83, 13, 24, 42, 14, 76],
+            explanations
+                .iter()
+                .filter_map(LineExplanation::value)
+                .collect::<Vec<i64>>()
+        );
+        assert_eq!(
+            Some(DigitMatch {
+                start: 7,
+                end: 11,
+                value: 4,
+                source: DigitSource::Word,
+            }),
+            explanations[3].last
+        );
+        assert_eq!(
+            "    4 | xtwone3four => 24
+      |  ^^^   ^^^^ first: word \"two\" at 1..4, last: word \"four\" at 7..11",
+            explanations[3].to_string()
+        );
+    }
+
+    #[test]
+    fn test_explain_part1() {
+        let explanations = explain("treb7uchet\nabc", Part::One(DigitClass::default()));
+
+        assert_eq!(Some(77), explanations[0].value());
+        assert_eq!(explanations[0].first, explanations[0].last);
+        assert_eq!(None, explanations[1].value());
+        assert_eq!("    2 | abc => no digit", explanations[1].to_string());
+    }
+
+    #[test]
+    fn test_explain_tens() {
+        let decoding = Decoding {
+            vocabulary: part2::Vocabulary::Extended,
+            ..Decoding::default()
+        };
+        let explanations = explain("sixty", Part::Two(decoding));
+
+        assert_eq!(Some(60), explanations[0].value());
+        assert_eq!(explanations[0].first, explanations[0].last);
+    }
+
+    #[test]
+    fn test_explain_line_numbers() {
+        // Leading blank lines are skipped but counted, like in the errors of `process`
+        let explanations = explain("\ntreb7uchet\r\n\n1", Part::Two(Decoding::default()));
+
+        assert_eq!(
+            vec![(2, Some(77)), (3, None), (4, Some(11))],
+            explanations
+                .iter()
+                .map(|explanation| (explanation.line_number, explanation.value()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_explain_rejected() {
+        let explanations = explain("a1b²c3\n1½", Part::One(DigitClass::Reject));
+
+        assert_eq!(vec![(3, '²')], explanations[0].rejected);
+        assert_eq!(None, explanations[0].value());
+        assert_eq!(
+            "    1 | a1b²c3 => rejected `²`
+      |    ^ not digits of the selected class",
+            explanations[0].to_string()
+        );
+
+        let explanations = explain("a1b²c3\n1٣", Part::One(DigitClass::Unicode));
+        assert_eq!(vec![(3, '²')], explanations[0].rejected);
+        assert_eq!(Some(13), explanations[1].value());
+        assert!(explain("a1b²c3", Part::One(DigitClass::Ascii))[0]
+            .rejected
+            .is_empty());
+    }
+}
@@ -1,25 +1,136 @@
-use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use std::str::FromStr;
 
-use crate::custom_error::AocError;
+use crate::{
+    custom_error::AocError,
+    digit::{decimal_value, DigitMatch, DigitSource},
+    stream::document_lines,
+};
 
-pub fn process(input: &str) -> miette::Result<i64, AocError> {
-    Ok(input
-        .trim_end()
-        .split('\n')
-        .map(|x| {
-            x.trim_end()
-                .chars()
-                .filter(|x| x.is_numeric())
-                .collect::<Vec<char>>()
-        })
-        .map(|x| {
-            let mut res = String::from(x[0]);
-            res.push(x[x.len() - 1]);
-            res.parse::<i64>().unwrap()
+// Which characters count as digits when decoding a line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DigitClass {
+    // Only `0` to `9` are digits, any other character is skipped
+    #[default]
+    Ascii,
+    // Decimal digits of any script are digits and converted to their value, other numeric
+    // characters such as `²` or `½` are rejected
+    Unicode,
+    // Only `0` to `9` are digits, any other numeric character is rejected
+    Reject,
+}
+
+impl FromStr for DigitClass {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ascii" => Ok(DigitClass::Ascii),
+            "unicode" => Ok(DigitClass::Unicode),
+            "reject" => Ok(DigitClass::Reject),
+            _ => Err(AocError::UnknownDigitClass(s.to_string())),
+        }
+    }
+}
+
+// Gives back the value of the character if it is a digit of the class
+fn class_digit(character: char, class: DigitClass) -> Option<u32> {
+    match class {
+        DigitClass::Unicode => decimal_value(character),
+        _ => character.to_digit(10),
+    }
+}
+
+// Computes the calibration value of a single line, if it holds any digit. The line is scanned
+// from the left for its first digit and from the right for its last one, without allocating.
+pub fn calibration_value(line: &str) -> Option<i64> {
+    let bytes = line.as_bytes();
+    let first = bytes.iter().find(|x| x.is_ascii_digit())?;
+    let last = bytes.iter().rfind(|x| x.is_ascii_digit())?;
+
+    Some(10 * (first - b'0') as i64 + (last - b'0') as i64)
+}
+
+// Computes the calibration value of a line given its number
+pub fn decode_line(line_number: usize, line: &str) -> miette::Result<i64, AocError> {
+    calibration_value(line).ok_or(AocError::NoDigit { line: line_number })
+}
+
+// Computes the calibration value of a line given its number, digits being taken from the given
+// class. Numeric characters outside of the class are reported unless the class is `Ascii`.
+pub fn decode_line_with(
+    line_number: usize,
+    line: &str,
+    class: DigitClass,
+) -> miette::Result<i64, AocError> {
+    if class == DigitClass::Ascii {
+        return decode_line(line_number, line);
+    }
+
+    let mut digits = None;
+    for (index, character) in line.trim_end().char_indices() {
+        match class_digit(character, class) {
+            Some(digit) => {
+                let (first, _) = digits.unwrap_or((digit, digit));
+                digits = Some((first, digit));
+            }
+            None if character.is_numeric() => {
+                return Err(AocError::UnexpectedNumeric {
+                    line: line_number,
+                    character,
+                    src: line.to_string(),
+                    span: (index, character.len_utf8()).into(),
+                })
+            }
+            None => (),
+        }
+    }
+
+    let (first, last) = digits.ok_or(AocError::NoDigit { line: line_number })?;
+    Ok(10 * first as i64 + last as i64)
+}
+
+// Finds every numeral of a line along with its position
+pub fn digit_matches(line: &str) -> Vec<DigitMatch> {
+    digit_matches_with(line, DigitClass::default())
+}
+
+// Finds every numeral of the given class in a line along with its position
+pub fn digit_matches_with(line: &str, class: DigitClass) -> Vec<DigitMatch> {
+    line.trim_end()
+        .char_indices()
+        .filter_map(|(index, character)| {
+            Some(DigitMatch {
+                start: index,
+                end: index + character.len_utf8(),
+                value: class_digit(character, class)? as i64,
+                source: DigitSource::Numeral,
+            })
         })
-        .collect::<Vec<i64>>()
-        .par_iter()
-        .sum())
+        .collect()
+}
+
+// Finds the numeric characters of a line the class rejects along with their positions. `Ascii`
+// skips them instead, so it rejects none.
+pub fn rejected_characters(line: &str, class: DigitClass) -> Vec<(usize, char)> {
+    if class == DigitClass::Ascii {
+        return vec![];
+    }
+    line.trim_end()
+        .char_indices()
+        .filter(|(_, character)| character.is_numeric() && class_digit(*character, class).is_none())
+        .collect()
+}
+
+pub fn process(input: &str) -> miette::Result<i64, AocError> {
+    document_lines(input)
+        .map(|(line_number, line)| decode_line(line_number, line))
+        .sum()
+}
+
+pub fn process_with(input: &str, class: DigitClass) -> miette::Result<i64, AocError> {
+    document_lines(input)
+        .map(|(line_number, line)| decode_line_with(line_number, line, class))
+        .sum()
 }
 
 #[cfg(test)]
@@ -35,4 +146,113 @@ treb7uchet";
         assert_eq!(142, process(input)?);
         Ok(())
     }
+
+    // The implementation this scanner replaced, collecting every numeric character of the line
+    fn collecting_calibration_value(line: &str) -> Option<i64> {
+        let digits = line
+            .trim_end()
+            .chars()
+            .filter(|x| x.is_numeric())
+            .collect::<Vec<char>>();
+
+        let mut res = String::from(*digits.first()?);
+        res.push(digits[digits.len() - 1]);
+        res.parse::<i64>().ok()
+    }
+
+    #[test]
+    fn test_calibration_value_matches_collecting() {
+        for line in include_str!("../input1.txt").lines() {
+            assert_eq!(collecting_calibration_value(line), calibration_value(line));
+        }
+
+        // Pseudo-random lines over a small alphabet, some of them without any digit
+        let alphabet = b"abcxyz 0123456789\r";
+        let mut state = 0x2023_u64;
+        for _ in 0..10_000 {
+            let line = (0..state % 13)
+                .map(|_| {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    alphabet[(state >> 33) as usize % alphabet.len()] as char
+                })
+                .collect::<String>();
+            assert_eq!(
+                collecting_calibration_value(&line),
+                calibration_value(&line)
+            );
+        }
+    }
+
+    #[test]
+    fn test_digit_classes() -> miette::Result<()> {
+        let input = "1abc2
+p٣r3stu8vwx
+a1b²c3d4e5f
+treb7uchet";
+
+        assert_eq!(142, process_with(input, DigitClass::Ascii)?);
+        assert!(matches!(
+            process_with(input, DigitClass::Unicode),
+            Err(AocError::UnexpectedNumeric {
+                line: 3,
+                character: '²',
+                ..
+            })
+        ));
+        assert!(matches!(
+            process_with(input, DigitClass::Reject),
+            Err(AocError::UnexpectedNumeric {
+                line: 2,
+                character: '٣',
+                ..
+            })
+        ));
+
+        let input = "1abc2
+p٣r3stu8vwx
+a1b2c3d4e5f
+treb7uchet";
+        assert_eq!(142, process_with(input, DigitClass::Unicode)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unicode_digits() -> miette::Result<()> {
+        // Adlam, outside of the basic multilingual plane
+        assert_eq!(12, decode_line_with(1, "1\u{1E951}2", DigitClass::Unicode)?);
+        assert_eq!(
+            91,
+            decode_line_with(1, "x\u{1E959}y\u{1E951}", DigitClass::Unicode)?
+        );
+
+        for (character, value) in [
+            ('\u{10D39}', 9),
+            ('\u{111D0}', 0),
+            ('\u{11453}', 3),
+            ('\u{11957}', 7),
+            ('\u{16A65}', 5),
+            ('\u{1FBF8}', 8),
+            // Added after Unicode 14
+            ('\u{11F53}', 3),
+            ('\u{1E4F0}', 0),
+            ('\u{10D40}', 0),
+            ('\u{16D79}', 9),
+            ('\u{11DE5}', 5),
+        ] {
+            assert_eq!(Some(value), decimal_value(character), "{character:?}");
+        }
+        assert_eq!(None, decimal_value('\u{1E95A}'));
+        assert_eq!(13, decode_line_with(1, "1\u{11F53}", DigitClass::Unicode)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unexpected_numeric_span() {
+        let Err(AocError::UnexpectedNumeric { src, span, .. }) =
+            decode_line_with(1, "ab½1", DigitClass::Reject)
+        else {
+            panic!("`½` should be rejected");
+        };
+        assert_eq!("½", &src[span.offset()..span.offset() + span.len()]);
+    }
 }
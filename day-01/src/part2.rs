@@ -1,53 +1,245 @@
-use std::collections::HashMap;
+use std::{cmp::Reverse, str::FromStr, sync::OnceLock};
 
-use rayon::{
-    iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator},
-    str::ParallelString,
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::{
+    custom_error::AocError,
+    digit::{last_match, DigitMatch, DigitSource},
+    stream::document_lines,
 };
 
-use crate::custom_error::AocError;
+const MAPPING: [(&str, i64); 18] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
 
-pub fn process(input: &str) -> miette::Result<i64, AocError> {
-    let mapping = HashMap::from([
-        ("one", 1),
-        ("two", 2),
-        ("three", 3),
-        ("four", 4),
-        ("five", 5),
-        ("six", 6),
-        ("seven", 7),
-        ("eight", 8),
-        ("nine", 9),
-        ("1", 1),
-        ("2", 2),
-        ("3", 3),
-        ("4", 4),
-        ("5", 5),
-        ("6", 6),
-        ("7", 7),
-        ("8", 8),
-        ("9", 9),
-    ]);
-
-    Ok(input
-        .trim()
-        .par_split('\n')
-        .into_par_iter()
-        .map(|elem: &str| {
-            let mut values = vec![];
+// How spelled digits sharing letters, like the `t` of `eightwo`, are decoded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverlapPolicy {
+    // Every match counts, so `eightwo` yields 8 and 2
+    #[default]
+    AllowOverlaps,
+    // Matches are taken left to right without overlapping, preferring the longest one when
+    // several start at the same position, so `eightwo` yields 8 only
+    LeftmostLongest,
+    // Matches are taken left to right without overlapping, preferring the one listed first in
+    // the vocabulary when several start at the same position. No word of the `Digits`
+    // vocabulary starts another, so it only differs from `LeftmostLongest` with `Extended`,
+    // where `seventeen` yields 77
+    LeftmostFirst,
+}
+
+impl FromStr for OverlapPolicy {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(OverlapPolicy::AllowOverlaps),
+            "longest" => Ok(OverlapPolicy::LeftmostLongest),
+            "first" => Ok(OverlapPolicy::LeftmostFirst),
+            _ => Err(AocError::UnknownOverlapPolicy(s.to_string())),
+        }
+    }
+}
+
+// Which spelled numbers are recognised in a line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Vocabulary {
+    // `one` to `nine`, as in the puzzle
+    #[default]
+    Digits,
+    // `zero` to `nine`, the teens, the tens and compounds such as `twentythree`, which are
+    // taken as multi-digit numbers
+    Extended,
+}
+
+impl FromStr for Vocabulary {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "digits" => Ok(Vocabulary::Digits),
+            "extended" => Ok(Vocabulary::Extended),
+            _ => Err(AocError::UnknownVocabulary(s.to_string())),
+        }
+    }
+}
+
+const UNITS: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const TEENS: [&str; 10] = [
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const TENS: [&str; 8] = [
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+// Lists zero to ninety-nine as words, followed by the numerals 0 to 9
+fn extended_mapping() -> &'static [(String, i64)] {
+    static MAPPING: OnceLock<Vec<(String, i64)>> = OnceLock::new();
 
-            for key in mapping.keys() {
-                for (index, value) in elem.match_indices(key) {
-                    values.push((index, mapping[value]));
-                }
+    MAPPING.get_or_init(|| {
+        let mut mapping = vec![];
+        for (value, unit) in UNITS.iter().enumerate() {
+            mapping.push((unit.to_string(), value as i64));
+        }
+        for (value, teen) in TEENS.iter().enumerate() {
+            mapping.push((teen.to_string(), 10 + value as i64));
+        }
+        for (tens, ten) in TENS.iter().enumerate() {
+            let tens = 10 * (tens as i64 + 2);
+            mapping.push((ten.to_string(), tens));
+            for (value, unit) in UNITS.iter().enumerate().skip(1) {
+                mapping.push((format!("{ten}{unit}"), tens + value as i64));
             }
+        }
+        for value in 0..10 {
+            mapping.push((value.to_string(), value));
+        }
+        mapping
+    })
+}
+
+impl Vocabulary {
+    fn mapping(&self) -> Vec<(&str, i64)> {
+        match self {
+            Vocabulary::Digits => MAPPING.to_vec(),
+            Vocabulary::Extended => extended_mapping()
+                .iter()
+                .map(|(key, value)| (key.as_str(), *value))
+                .collect(),
+        }
+    }
+}
+
+// How a line of the document is decoded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Decoding {
+    pub vocabulary: Vocabulary,
+    pub overlap: OverlapPolicy,
+}
+
+// Finds every digit of a line, spelled or not, ordered by position
+pub fn digit_matches(line: &str) -> Vec<DigitMatch> {
+    digit_matches_with(line, Decoding::default())
+}
 
-            values.sort();
-            10 * values[0].1 + values[values.len() - 1].1
+// Finds the numbers of a line, spelled or not, ordered by position and kept according to the
+// overlap policy. Among matches starting at the same position, the longest comes first.
+pub fn digit_matches_with(line: &str, decoding: Decoding) -> Vec<DigitMatch> {
+    let mut values = vec![];
+
+    for (rank, (key, value)) in decoding.vocabulary.mapping().into_iter().enumerate() {
+        for (index, text) in line.match_indices(key) {
+            let digit_match = DigitMatch {
+                start: index,
+                end: index + text.len(),
+                value,
+                source: if text.len() == 1 {
+                    DigitSource::Numeral
+                } else {
+                    DigitSource::Word
+                },
+            };
+            values.push((digit_match, rank));
+        }
+    }
+
+    match decoding.overlap {
+        OverlapPolicy::AllowOverlaps => {
+            values.sort_by_key(|(value, _)| (value.start, Reverse(value.end)));
+            return values.into_iter().map(|(value, _)| value).collect();
+        }
+        OverlapPolicy::LeftmostLongest => {
+            values.sort_by_key(|(value, _)| (value.start, Reverse(value.end)));
+        }
+        OverlapPolicy::LeftmostFirst => {
+            values.sort_by_key(|(value, rank)| (value.start, *rank));
+        }
+    }
+
+    // The preferred match of each position comes first, anything overlapping it is dropped
+    let mut cursor = 0;
+    values
+        .into_iter()
+        .map(|(value, _)| value)
+        .filter(|value| {
+            let keep = value.start >= cursor;
+            if keep {
+                cursor = value.end;
+            }
+            keep
         })
-        .collect::<Vec<i64>>()
-        .par_iter()
-        .sum::<i64>())
+        .collect()
+}
+
+// Computes the calibration value of a single line, spelled digits included
+pub fn calibration_value(line: &str) -> Option<i64> {
+    calibration_value_with(line, Decoding::default())
+}
+
+// Computes the calibration value of a single line with the given decoding. A multi-digit number
+// counts as the digits it is written with, so the first digit of `twentythree` is 2 and its
+// last digit is 3.
+pub fn calibration_value_with(line: &str, decoding: Decoding) -> Option<i64> {
+    let values = digit_matches_with(line, decoding);
+    Some(10 * values.first()?.leading_digit() + last_match(&values)?.trailing_digit())
+}
+
+// Computes the calibration value of a line given its number, spelled digits included
+pub fn decode_line(line_number: usize, line: &str) -> miette::Result<i64, AocError> {
+    decode_line_with(line_number, line, Decoding::default())
+}
+
+// Computes the calibration value of a line given its number with the given decoding
+pub fn decode_line_with(
+    line_number: usize,
+    line: &str,
+    decoding: Decoding,
+) -> miette::Result<i64, AocError> {
+    calibration_value_with(line, decoding).ok_or(AocError::NoDigit { line: line_number })
+}
+
+pub fn process(input: &str) -> miette::Result<i64, AocError> {
+    process_with(input, Decoding::default())
+}
+
+// Lines are decoded in parallel but summed in order, so that the first line failing to decode is
+// the one reported
+pub fn process_with(input: &str, decoding: Decoding) -> miette::Result<i64, AocError> {
+    document_lines(input)
+        .collect::<Vec<(usize, &str)>>()
+        .into_par_iter()
+        .map(|(line_number, line)| decode_line_with(line_number, line, decoding))
+        .collect::<Vec<Result<i64, AocError>>>()
+        .into_iter()
+        .sum()
 }
 
 #[cfg(test)]
@@ -66,4 +258,136 @@ zoneight234
         assert_eq!(281, process(input)?);
         Ok(())
     }
+
+    #[test]
+    fn test_overlap_policies() {
+        let cases = [
+            // line, allow overlaps, leftmost-longest, leftmost-first
+            ("eightwo", 82, 88, 88),
+            ("twone", 21, 22, 22),
+            ("oneight", 18, 11, 11),
+            ("eightwothree", 83, 83, 83),
+            ("xtwone3four", 24, 24, 24),
+            ("zoneight234", 14, 14, 14),
+            ("sevenine", 79, 77, 77),
+        ];
+
+        for (line, allow, longest, first) in cases {
+            use OverlapPolicy::*;
+            let with = |overlap| Decoding {
+                overlap,
+                ..Default::default()
+            };
+            assert_eq!(
+                Some(allow),
+                calibration_value_with(line, with(AllowOverlaps)),
+                "{line}"
+            );
+            assert_eq!(
+                Some(longest),
+                calibration_value_with(line, with(LeftmostLongest)),
+                "{line}"
+            );
+            assert_eq!(
+                Some(first),
+                calibration_value_with(line, with(LeftmostFirst)),
+                "{line}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_with() -> miette::Result<()> {
+        let input = "two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen
+eightwo";
+        let with = |overlap| Decoding {
+            overlap,
+            ..Default::default()
+        };
+        assert_eq!(
+            363,
+            process_with(input, with(OverlapPolicy::AllowOverlaps))?
+        );
+        assert_eq!(
+            369,
+            process_with(input, with(OverlapPolicy::LeftmostLongest))?
+        );
+        assert_eq!(
+            369,
+            process_with(input, with(OverlapPolicy::LeftmostFirst))?
+        );
+
+        // Only words starting other words, like `six` in `sixteen`, tell the leftmost policies apart
+        let extended = |overlap| Decoding {
+            vocabulary: Vocabulary::Extended,
+            overlap,
+        };
+        assert_eq!(
+            74,
+            process_with("sixteen\nfifty8", extended(OverlapPolicy::LeftmostLongest))?
+        );
+        assert_eq!(
+            124,
+            process_with("sixteen\nfifty8", extended(OverlapPolicy::LeftmostFirst))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_extended_vocabulary() {
+        let cases = [
+            // line, allow overlaps, leftmost-longest, leftmost-first
+            ("twentythree", 23, 23, 23),
+            ("xseventeeny", 17, 17, 77),
+            ("zero5", 5, 5, 5),
+            ("ninetynine", 99, 99, 99),
+            ("sixty", 60, 60, 66),
+            ("seventy", 70, 70, 77),
+            ("xeightyz", 80, 80, 88),
+            ("twelvefortytwo", 12, 12, 12),
+            ("eleventhirty", 10, 10, 10),
+            ("7pqrstsixteen", 76, 76, 76),
+        ];
+
+        for (line, allow, longest, first) in cases {
+            use OverlapPolicy::*;
+            let with = |overlap| Decoding {
+                vocabulary: Vocabulary::Extended,
+                overlap,
+            };
+            assert_eq!(
+                Some(allow),
+                calibration_value_with(line, with(AllowOverlaps)),
+                "{line}"
+            );
+            assert_eq!(
+                Some(longest),
+                calibration_value_with(line, with(LeftmostLongest)),
+                "{line}"
+            );
+            assert_eq!(
+                Some(first),
+                calibration_value_with(line, with(LeftmostFirst)),
+                "{line}"
+            );
+        }
+
+        let decoding = Decoding {
+            vocabulary: Vocabulary::Extended,
+            overlap: OverlapPolicy::LeftmostLongest,
+        };
+        assert_eq!(
+            vec![12, 42],
+            digit_matches_with("twelvefortytwo", decoding)
+                .iter()
+                .map(|value| value.value)
+                .collect::<Vec<i64>>()
+        );
+    }
 }
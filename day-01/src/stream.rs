@@ -0,0 +1,236 @@
+use std::io::BufRead;
+
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use crate::custom_error::AocError;
+
+// Splits a document held in memory into its lines along with their number. Blank lines at the
+// start and at the end of the document are left out, the others keeping their number in the
+// document so that errors point at the right line.
+pub fn document_lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .trim_end()
+        .split('\n')
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .skip_while(|(_, line)| line.trim().is_empty())
+}
+
+// Sums the calibration values of a document read line by line, so that only the line being
+// decoded is kept in memory. Blank lines at the start and at the end of the document are ignored
+// and lines keep their number in the document, like `document_lines` does.
+//
+// `decode` computes the calibration value of a line given its number, like `part1::decode_line`.
+pub fn process_reader<R, D>(mut reader: R, decode: D) -> miette::Result<i64, AocError>
+where
+    R: BufRead,
+    D: Fn(usize, &str) -> miette::Result<i64, AocError>,
+{
+    let mut line = String::new();
+    let mut line_number = 0;
+    let mut pending_blank = None;
+    let mut started = false;
+    let mut res = 0;
+
+    while reader.read_line(&mut line)? != 0 {
+        line_number += 1;
+
+        if line.trim().is_empty() {
+            if started {
+                pending_blank.get_or_insert(line_number);
+            }
+        } else if let Some(blank) = pending_blank.take() {
+            return Err(AocError::NoDigit { line: blank });
+        } else {
+            started = true;
+            res += decode(line_number, &line)?;
+        }
+
+        line.clear();
+    }
+
+    Ok(res)
+}
+
+// Same as `process_reader`, but reads the document in chunks of `chunk_lines` lines whose
+// values are decoded in parallel. Memory stays bounded by the size of a chunk. When several lines
+// fail to decode, the first of them is reported, as `process_reader` does.
+pub fn process_reader_par<R, D>(
+    reader: R,
+    chunk_lines: usize,
+    decode: D,
+) -> miette::Result<i64, AocError>
+where
+    R: BufRead,
+    D: Fn(usize, &str) -> miette::Result<i64, AocError> + Sync,
+{
+    let mut lines = reader.lines();
+    let mut chunk = Vec::with_capacity(chunk_lines);
+    let mut first_line_number = 1;
+    let mut pending_blank = None;
+    let mut started = false;
+    let mut res = 0;
+
+    loop {
+        chunk.clear();
+        for line in lines.by_ref().take(chunk_lines.max(1)) {
+            chunk.push(line?);
+        }
+        if chunk.is_empty() {
+            break;
+        }
+
+        // Blank lines before the first line with content are skipped
+        let leading_blanks = if started {
+            0
+        } else {
+            chunk
+                .iter()
+                .take_while(|line| line.trim().is_empty())
+                .count()
+        };
+        let lines = &chunk[leading_blanks..];
+        let lines_start = first_line_number + leading_blanks;
+        started |= !lines.is_empty();
+
+        // A blank line is only an error if some content follows it
+        if let Some(blank) = pending_blank {
+            if lines.iter().any(|line| !line.trim().is_empty()) {
+                return Err(AocError::NoDigit { line: blank });
+            }
+        }
+        let trailing_blanks = lines
+            .iter()
+            .rev()
+            .take_while(|line| line.trim().is_empty())
+            .count();
+        let content = &lines[..lines.len() - trailing_blanks];
+
+        // Values are decoded in parallel but summed in order, so that the first error is kept
+        res += content
+            .par_iter()
+            .enumerate()
+            .map(|(index, line)| decode(lines_start + index, line))
+            .collect::<Vec<Result<i64, AocError>>>()
+            .into_iter()
+            .sum::<Result<i64, AocError>>()?;
+
+        pending_blank = match (content.is_empty(), pending_blank) {
+            (true, Some(blank)) => Some(blank),
+            _ if trailing_blanks > 0 => Some(lines_start + content.len()),
+            _ => None,
+        };
+        first_line_number += chunk.len();
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{part1, part2};
+
+    const INPUT: &str = include_str!("../input1.txt");
+
+    #[test]
+    fn test_process_reader() -> miette::Result<()> {
+        assert_eq!(
+            part1::process(INPUT)?,
+            process_reader(INPUT.as_bytes(), part1::decode_line)?
+        );
+        assert_eq!(
+            part2::process(INPUT)?,
+            process_reader(INPUT.as_bytes(), part2::decode_line)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_reader_par() -> miette::Result<()> {
+        for chunk_lines in [1, 7, 64, 5000] {
+            assert_eq!(
+                part1::process(INPUT)?,
+                process_reader_par(INPUT.as_bytes(), chunk_lines, part1::decode_line)?
+            );
+            assert_eq!(
+                part2::process(INPUT)?,
+                process_reader_par(INPUT.as_bytes(), chunk_lines, part2::decode_line)?
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_blank_lines() {
+        let input = "1abc2\n\ntreb7uchet\n\n\n";
+        for chunk_lines in [1, 2, 3, 64] {
+            assert!(matches!(
+                process_reader_par(input.as_bytes(), chunk_lines, part1::decode_line),
+                Err(AocError::NoDigit { line: 2 })
+            ));
+        }
+        assert!(matches!(
+            process_reader(input.as_bytes(), part1::decode_line),
+            Err(AocError::NoDigit { line: 2 })
+        ));
+        assert_eq!(
+            Some(89),
+            process_reader_par("1abc2\ntreb7uchet\n\n\n".as_bytes(), 2, part1::decode_line).ok()
+        );
+    }
+
+    #[test]
+    fn test_leading_blank_lines() -> miette::Result<()> {
+        let input = "\n  \ntwo1nine\n";
+        assert_eq!(29, part2::process(input)?);
+        assert_eq!(29, process_reader(input.as_bytes(), part2::decode_line)?);
+        for chunk_lines in [1, 2, 64] {
+            assert_eq!(
+                29,
+                process_reader_par(input.as_bytes(), chunk_lines, part2::decode_line)?
+            );
+        }
+        assert_eq!(11, part1::process(input)?);
+
+        // Lines keep their number in the document whether it is streamed or not
+        let input = "\n\n1abc2\n\ntreb7uchet";
+        assert!(matches!(
+            part2::process(input),
+            Err(AocError::NoDigit { line: 4 })
+        ));
+        assert!(matches!(
+            process_reader(input.as_bytes(), part2::decode_line),
+            Err(AocError::NoDigit { line: 4 })
+        ));
+        for chunk_lines in [1, 2, 3, 64] {
+            assert!(matches!(
+                process_reader_par(input.as_bytes(), chunk_lines, part2::decode_line),
+                Err(AocError::NoDigit { line: 4 })
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_first_error() {
+        // Several lines fail, the first of them is the one reported
+        let input = (1..=2000)
+            .map(|number| match number % 500 {
+                0 => "x".to_string(),
+                _ => number.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        for _ in 0..20 {
+            assert!(matches!(
+                part2::process(&input),
+                Err(AocError::NoDigit { line: 500 })
+            ));
+            assert!(matches!(
+                process_reader_par(input.as_bytes(), 1000, part2::decode_line),
+                Err(AocError::NoDigit { line: 500 })
+            ));
+        }
+    }
+}
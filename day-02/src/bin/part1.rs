@@ -1,10 +1,100 @@
-use day_02::part1::process;
-use miette::Context;
+use std::collections::BTreeSet;
+
+use day_02::{
+    export::{check_colours, from_json, to_csv, to_json},
+    game::{parse_games_with, CubeSet, UnknownColours},
+    infer::{infer_bags, minimal_bag},
+    part1::{puzzle_bag, solve},
+    report::feasibility_report,
+};
+use miette::{Context, IntoDiagnostic};
 
 fn main() -> miette::Result<()> {
     let file = include_str!("../../input1.txt");
 
-    let result = process(file).context("process part 1")?;
+    // The bag is given either as `--bag red=12,green=13,blue=14` or as a file holding the same
+    // description with `--bag-file <path>`, and defaults to the puzzle's
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let value_of = |name: &str| {
+        let index = args.iter().position(|arg| arg == name)?;
+        args.get(index + 1)
+    };
+    let bag = match (value_of("--bag"), value_of("--bag-file")) {
+        (Some(bag), _) => bag.parse::<CubeSet>()?,
+        (None, Some(path)) => std::fs::read_to_string(path)
+            .into_diagnostic()
+            .with_context(|| format!("read {path}"))?
+            .parse::<CubeSet>()?,
+        (None, None) => puzzle_bag(),
+    };
+
+    // `--strict` rejects the colours the bag does not hold instead of deeming their games
+    // impossible
+    let unknown = if args.iter().any(|arg| arg == "--strict") {
+        UnknownColours::Reject
+    } else {
+        UnknownColours::Allow
+    };
+
+    // `--json <path>` loads the games from an exported JSON log instead of the puzzle input
+    let games = match value_of("--json") {
+        Some(path) => {
+            let json = std::fs::read_to_string(path)
+                .into_diagnostic()
+                .with_context(|| format!("read {path}"))?;
+            let games = from_json(&json).context("load part 1")?;
+            if unknown == UnknownColours::Reject {
+                check_colours(&games, &bag).context("load part 1")?;
+            }
+            games
+        }
+        None => parse_games_with(file, &bag, unknown).context("parse part 1")?,
+    };
+
+    // `--export-json <path>` and `--export-csv <path>` write the games for other tools
+    if let Some(path) = value_of("--export-json") {
+        std::fs::write(path, to_json(&games)?)
+            .into_diagnostic()
+            .with_context(|| format!("write {path}"))?;
+    }
+    if let Some(path) = value_of("--export-csv") {
+        std::fs::write(path, to_csv(&games))
+            .into_diagnostic()
+            .with_context(|| format!("write {path}"))?;
+    }
+
+    // `--infer <id,id,...>` looks for the bags making exactly those games possible, the smallest
+    // one and, with `--budget <cubes>`, every one holding at most that many cubes
+    if let Some(ids) = value_of("--infer") {
+        let possible = ids
+            .split(',')
+            .map(|id| id.trim().parse::<u32>())
+            .collect::<Result<BTreeSet<u32>, _>>()
+            .into_diagnostic()
+            .context("parse --infer")?;
+        let budget = value_of("--budget")
+            .map(|budget| budget.parse::<u32>())
+            .transpose()
+            .into_diagnostic()
+            .context("parse --budget")?;
+
+        match minimal_bag(&games, &possible, budget.unwrap_or(u32::MAX))? {
+            Some(bag) => println!("Smallest bag: {bag}"),
+            None => println!("No bag makes exactly these games possible"),
+        }
+        if let Some(budget) = budget {
+            for bag in infer_bags(&games, &possible, budget)? {
+                println!("{bag}");
+            }
+        }
+    }
+
+    // `--report` details which rounds of the impossible games exceed the bag
+    if args.iter().any(|arg| arg == "--report") {
+        print!("{}", feasibility_report(&games, &bag));
+    }
+
+    let result = solve(&games, &bag);
     println!("{}", result);
 
     Ok(())
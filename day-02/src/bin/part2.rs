@@ -1,10 +1,49 @@
-use day_02::part2::process;
-use miette::Context;
+use day_02::{
+    export::{from_json, to_csv, to_json},
+    game::parse_games,
+    part2::solve,
+    report::minimal_set_table,
+};
+use miette::{Context, IntoDiagnostic};
 
 fn main() -> miette::Result<()> {
     let file = include_str!("../../input1.txt");
 
-    let result = process(file).context("process part 2")?;
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let value_of = |name: &str| {
+        let index = args.iter().position(|arg| arg == name)?;
+        args.get(index + 1)
+    };
+
+    // `--json <path>` loads the games from an exported JSON log instead of the puzzle input
+    let games = match value_of("--json") {
+        Some(path) => {
+            let json = std::fs::read_to_string(path)
+                .into_diagnostic()
+                .with_context(|| format!("read {path}"))?;
+            from_json(&json).context("load part 2")?
+        }
+        None => parse_games(file).context("parse part 2")?,
+    };
+
+    // `--export-json <path>` and `--export-csv <path>` write the games for other tools
+    if let Some(path) = value_of("--export-json") {
+        std::fs::write(path, to_json(&games)?)
+            .into_diagnostic()
+            .with_context(|| format!("write {path}"))?;
+    }
+    if let Some(path) = value_of("--export-csv") {
+        std::fs::write(path, to_csv(&games))
+            .into_diagnostic()
+            .with_context(|| format!("write {path}"))?;
+    }
+
+    // `--report` prints the minimal set of every game along with its power
+    if args.iter().any(|arg| arg == "--report") {
+        print!("{}", minimal_set_table(&games).context("report part 2")?);
+    }
+
+    let result = solve(&games).context("process part 2")?;
     println!("{}", result);
 
     Ok(())
@@ -1,4 +1,4 @@
-use miette::Diagnostic;
+use miette::{Diagnostic, SourceSpan};
 use thiserror::Error;
 
 #[derive(Error, Diagnostic, Debug)]
@@ -6,4 +6,68 @@ pub enum AocError {
     #[error(transparent)]
     #[diagnostic(code(aoc::io_error))]
     IoError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    #[diagnostic(code(aoc::json_error))]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("invalid game log: {0}")]
+    #[diagnostic(code(aoc::invalid_game_log))]
+    InvalidGameLog(String),
+
+    #[error("game {0} is not part of the log")]
+    #[diagnostic(code(aoc::unknown_game_id))]
+    UnknownGameId(u32),
+
+    #[error("invalid game record: {reason}")]
+    #[diagnostic(code(aoc::invalid_game))]
+    InvalidGame {
+        reason: String,
+        #[source_code]
+        src: String,
+        #[label("{reason}")]
+        span: SourceSpan,
+    },
+
+    #[error("invalid bag: {reason}")]
+    #[diagnostic(
+        code(aoc::invalid_bag),
+        help("a bag is described as `red=12,green=13,blue=14`")
+    )]
+    InvalidBag {
+        reason: String,
+        #[source_code]
+        src: String,
+        #[label("{reason}")]
+        span: SourceSpan,
+    },
+
+    #[error("game {id} is declared more than once")]
+    #[diagnostic(code(aoc::duplicate_game_id))]
+    DuplicateGameId {
+        id: u32,
+        #[source_code]
+        src: String,
+        #[label("first declared here")]
+        first: SourceSpan,
+        #[label("declared again here")]
+        second: SourceSpan,
+    },
+
+    #[error("colour `{colour}` is not held by the bag")]
+    #[diagnostic(
+        code(aoc::unknown_colour),
+        help("add the colour to the bag or allow unknown colours")
+    )]
+    UnknownColour {
+        colour: String,
+        #[source_code]
+        src: String,
+        #[label("unknown colour")]
+        span: SourceSpan,
+    },
+
+    #[error("{what} does not fit in 64 bits")]
+    #[diagnostic(code(aoc::overflow))]
+    Overflow { what: String },
 }
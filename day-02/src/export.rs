@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+
+use crate::{
+    custom_error::AocError,
+    game::{is_colour, CubeSet, Game},
+};
+
+// Writes the games as a JSON array such as
+//     [{ "id": 1, "rounds": [{ "blue": 3, "red": 4 }, { "green": 2 }] }]
+pub fn to_json(games: &[Game]) -> miette::Result<String, AocError> {
+    Ok(serde_json::to_string_pretty(games)?)
+}
+
+// Loads games written by `to_json`, checking them the way the text parser would
+pub fn from_json(json: &str) -> miette::Result<Vec<Game>, AocError> {
+    let games = serde_json::from_str::<Vec<Game>>(json)?;
+
+    let mut ids = HashSet::new();
+    for game in games.iter() {
+        if !ids.insert(game.id) {
+            return Err(AocError::InvalidGameLog(format!(
+                "game {} is declared more than once",
+                game.id
+            )));
+        }
+        let colours = game.rounds.iter().flat_map(CubeSet::colours);
+        if let Some(colour) = colours.into_iter().find(|colour| !is_colour(colour)) {
+            return Err(AocError::InvalidGameLog(format!(
+                "game {} shows `{colour}`, which is not an identifier",
+                game.id
+            )));
+        }
+    }
+
+    Ok(games)
+}
+
+// Checks that the games only show colours the bag holds, as `UnknownColours::Reject` does when
+// parsing a text log
+pub fn check_colours(games: &[Game], bag: &CubeSet) -> miette::Result<(), AocError> {
+    for game in games.iter() {
+        let colours = game.rounds.iter().flat_map(CubeSet::colours);
+        if let Some(colour) = colours.into_iter().find(|colour| !bag.contains(colour)) {
+            return Err(AocError::InvalidGameLog(format!(
+                "game {} shows `{colour}`, which the bag does not hold",
+                game.id
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Writes the games as tidy CSV, one row per colour of each round, rounds counting from 1. A
+// round showing no cube at all has no row.
+pub fn to_csv(games: &[Game]) -> String {
+    let mut csv = String::from("game,round,colour,count\n");
+    for game in games.iter() {
+        for (index, round) in game.rounds.iter().enumerate() {
+            for (colour, count) in round.iter() {
+                csv += &format!("{},{},{colour},{count}\n", game.id, index + 1);
+            }
+        }
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::parse_games, part1, part2};
+
+    const INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn test_json_round_trip() -> miette::Result<()> {
+        let games = parse_games(INPUT)?;
+        let loaded = from_json(&to_json(&games)?)?;
+
+        assert_eq!(games, loaded);
+        assert_eq!(8, part1::solve(&loaded, &part1::puzzle_bag()));
+        assert_eq!(2286, part2::solve(&loaded)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        let duplicate = r#"[{ "id": 1, "rounds": [] }, { "id": 1, "rounds": [] }]"#;
+        assert!(matches!(
+            from_json(duplicate),
+            Err(AocError::InvalidGameLog(_))
+        ));
+
+        let colour = r#"[{ "id": 1, "rounds": [{ "dark blue": 1 }] }]"#;
+        assert!(matches!(
+            from_json(colour),
+            Err(AocError::InvalidGameLog(_))
+        ));
+
+        assert!(matches!(
+            from_json(r#"[{ "id": "one" }]"#),
+            Err(AocError::JsonError(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_csv() -> miette::Result<()> {
+        let games = parse_games("Game 7: 3 blue, 4 red; 2 green\nGame 9: 1 teal")?;
+
+        assert_eq!(
+            "game,round,colour,count
+7,1,blue,3
+7,1,red,4
+7,2,green,2
+9,1,teal,1
+",
+            to_csv(&games)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,498 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    str::FromStr,
+};
+
+use miette::SourceSpan;
+use serde::{Deserialize, Serialize};
+
+use crate::custom_error::AocError;
+
+// Cubes shown in a round or held in a bag, by colour. A colour that is not listed counts as zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CubeSet {
+    counts: BTreeMap<String, u32>,
+}
+
+impl CubeSet {
+    // Gives back how many cubes of the colour the set holds
+    pub fn get(&self, colour: &str) -> u32 {
+        self.counts.get(colour).copied().unwrap_or(0)
+    }
+
+    pub fn contains(&self, colour: &str) -> bool {
+        self.counts.contains_key(colour)
+    }
+
+    pub fn set(&mut self, colour: &str, count: u32) {
+        self.counts.insert(colour.to_string(), count);
+    }
+
+    pub fn add(&mut self, colour: &str, count: u32) {
+        *self.counts.entry(colour.to_string()).or_insert(0) += count;
+    }
+
+    // Iterates over the colours of the set and their counts, ordered by colour
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.counts
+            .iter()
+            .map(|(colour, count)| (colour.as_str(), *count))
+    }
+
+    pub fn colours(&self) -> impl Iterator<Item = &str> {
+        self.counts.keys().map(String::as_str)
+    }
+
+    // Tells whether there are enough cubes of every colour in the bag to show this set
+    pub fn fits_in(&self, bag: &CubeSet) -> bool {
+        self.iter().all(|(colour, count)| count <= bag.get(colour))
+    }
+
+    // Gives back, for every colour of either set, the largest count of both sets
+    pub fn max(&self, other: &CubeSet) -> CubeSet {
+        let mut res = self.clone();
+        for (colour, count) in other.iter() {
+            let current = res.get(colour);
+            res.set(colour, current.max(count));
+        }
+        res
+    }
+
+    // Multiplies the counts of the given colours together, a colour missing from the set making
+    // the power zero. Gives back `None` if the power does not fit in 64 bits.
+    pub fn power<'a>(&self, colours: impl IntoIterator<Item = &'a str>) -> Option<u64> {
+        colours.into_iter().try_fold(1_u64, |power, colour| {
+            power.checked_mul(self.get(colour) as u64)
+        })
+    }
+}
+
+impl<const N: usize> From<[(&str, u32); N]> for CubeSet {
+    fn from(counts: [(&str, u32); N]) -> Self {
+        let mut set = CubeSet::default();
+        for (colour, count) in counts {
+            set.add(colour, count);
+        }
+        set
+    }
+}
+
+// Writes the set the way bags are described, e.g. `blue=14, green=13, red=12`
+impl fmt::Display for CubeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = self
+            .iter()
+            .map(|(colour, count)| format!("{colour}={count}"))
+            .collect::<Vec<String>>();
+        write!(f, "{}", counts.join(", "))
+    }
+}
+
+// Parses a number written with ASCII digits only, rejecting the sign `str::parse` would accept
+fn parse_digits(text: &str) -> Option<u32> {
+    Some(text)
+        .filter(|text| text.bytes().all(|x| x.is_ascii_digit()))
+        .and_then(|text| text.parse::<u32>().ok())
+}
+
+// Colours are identifiers, such as `red` or `dark_blue`
+pub fn is_colour(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|character| character.is_alphanumeric() || character == '_' || character == '-')
+}
+
+// Parses a bag description such as `red=12, green=13, blue=14`. Entries are separated by commas
+// or new lines, and lines starting with `#` are comments, so that the same syntax can be used
+// on the command line and in a configuration file. Any colour can be listed, those left out hold
+// no cube.
+impl FromStr for CubeSet {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |piece: Spanned, reason: &str| AocError::InvalidBag {
+            reason: reason.to_string(),
+            src: s.to_string(),
+            span: piece.span(),
+        };
+        let mut bag = CubeSet::default();
+
+        let lines = Spanned { offset: 0, text: s }
+            .split('\n')
+            .filter(|line| !line.text.trim_start().starts_with('#'));
+        for entry in lines.flat_map(|line| line.split(',')).map(Spanned::trim) {
+            if entry.text.is_empty() {
+                continue;
+            }
+            let (colour, count) = entry
+                .split_once('=')
+                .ok_or_else(|| error(entry, "expected `<colour>=<count>`"))?;
+            let (colour, count) = (colour.trim(), count.trim());
+            let count = parse_digits(count.text)
+                .ok_or_else(|| error(count, "cube count is not a number"))?;
+
+            if !is_colour(colour.text) {
+                return Err(error(colour, "colour is not an identifier"));
+            }
+            bag.set(colour.text, count);
+        }
+
+        Ok(bag)
+    }
+}
+
+pub type Round = CubeSet;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Game {
+    pub id: u32,
+    pub rounds: Vec<Round>,
+}
+
+impl Game {
+    // Tells whether every round of the game could have been drawn from the bag
+    pub fn is_possible_with(&self, bag: &CubeSet) -> bool {
+        self.rounds.iter().all(|round| round.fits_in(bag))
+    }
+
+    // Computes the fewest cubes of each colour shown the bag must hold for the game to be possible
+    pub fn minimal_set(&self) -> CubeSet {
+        self.rounds
+            .iter()
+            .fold(CubeSet::default(), |acc, round| acc.max(round))
+    }
+}
+
+// Lists every colour shown in the log, ordered by name
+pub fn colours_of(games: &[Game]) -> Vec<String> {
+    games
+        .iter()
+        .flat_map(|game| game.rounds.iter())
+        .flat_map(|round| round.colours())
+        .collect::<BTreeSet<&str>>()
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+// A piece of the input along with its byte offset from the start of the input
+#[derive(Debug, Clone, Copy)]
+struct Spanned<'a> {
+    offset: usize,
+    text: &'a str,
+}
+
+impl<'a> Spanned<'a> {
+    fn trim(self) -> Spanned<'a> {
+        let start = self.text.len() - self.text.trim_start().len();
+        Spanned {
+            offset: self.offset + start,
+            text: self.text.trim(),
+        }
+    }
+
+    fn split(self, separator: char) -> impl Iterator<Item = Spanned<'a>> {
+        let mut offset = self.offset;
+        self.text.split(separator).map(move |text| {
+            let piece = Spanned { offset, text };
+            offset += text.len() + separator.len_utf8();
+            piece
+        })
+    }
+
+    fn split_once(self, separator: char) -> Option<(Spanned<'a>, Spanned<'a>)> {
+        let (left, right) = self.text.split_once(separator)?;
+        Some((
+            Spanned {
+                offset: self.offset,
+                text: left,
+            },
+            Spanned {
+                offset: self.offset + left.len() + separator.len_utf8(),
+                text: right,
+            },
+        ))
+    }
+
+    fn span(&self) -> SourceSpan {
+        (self.offset, self.text.len()).into()
+    }
+}
+
+// What the parser does with a colour the bag does not hold
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnknownColours {
+    // Any colour is accepted, a game showing a colour the bag does not hold being impossible
+    #[default]
+    Allow,
+    // Colours the bag does not hold are reported as errors
+    Reject,
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    known: Option<&'a CubeSet>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, piece: Spanned, reason: &str) -> AocError {
+        AocError::InvalidGame {
+            reason: reason.to_string(),
+            src: self.input.to_string(),
+            span: piece.span(),
+        }
+    }
+
+    // Parses `<count> <colour>` into the round
+    fn parse_cubes(&self, cubes: Spanned, round: &mut Round) -> miette::Result<(), AocError> {
+        let (count, colour) = cubes
+            .split_once(' ')
+            .ok_or_else(|| self.error(cubes, "expected `<count> <colour>`"))?;
+        let count = parse_digits(count.text)
+            .ok_or_else(|| self.error(count, "cube count is not a number"))?;
+
+        let colour = colour.trim();
+        if !is_colour(colour.text) {
+            return Err(self.error(colour, "colour is not an identifier"));
+        }
+        if let Some(bag) = self.known {
+            if !bag.contains(colour.text) {
+                return Err(AocError::UnknownColour {
+                    colour: colour.text.to_string(),
+                    src: self.input.to_string(),
+                    span: colour.span(),
+                });
+            }
+        }
+
+        // The same colour may be listed more than once in a round, its counts adding up
+        let total = round
+            .get(colour.text)
+            .checked_add(count)
+            .ok_or_else(|| self.error(cubes, "too many cubes of the colour in the round"))?;
+        round.set(colour.text, total);
+        Ok(())
+    }
+
+    // Parses `Game <id>: <round>; <round>; ...`, giving back the game and where its id is
+    fn parse_game(&self, line: Spanned<'a>) -> miette::Result<(Game, Spanned<'a>), AocError> {
+        let (header, rounds) = line
+            .split_once(':')
+            .ok_or_else(|| self.error(line, "expected `Game <id>:` before the rounds"))?;
+        let header = header.trim();
+        let id = header
+            .text
+            .strip_prefix("Game")
+            .map(|id| Spanned {
+                offset: header.offset + "Game".len(),
+                text: id,
+            })
+            .ok_or_else(|| self.error(header, "expected `Game <id>`"))?
+            .trim();
+        let id_span = id;
+        let id = parse_digits(id.text).ok_or_else(|| self.error(id, "game id is not a number"))?;
+
+        let rounds = rounds
+            .split(';')
+            .map(|round| {
+                let mut cubes = Round::default();
+                for entry in round.split(',') {
+                    self.parse_cubes(entry.trim(), &mut cubes)?;
+                }
+                Ok(cubes)
+            })
+            .collect::<Result<Vec<Round>, AocError>>()?;
+
+        Ok((Game { id, rounds }, id_span))
+    }
+}
+
+// Parses a game log, one game per line. Blank lines are skipped.
+pub fn parse_games(input: &str) -> miette::Result<Vec<Game>, AocError> {
+    parse_games_with(input, &CubeSet::default(), UnknownColours::Allow)
+}
+
+// Parses a game log, checking the colours shown against those of the bag if asked to
+pub fn parse_games_with(
+    input: &str,
+    bag: &CubeSet,
+    unknown: UnknownColours,
+) -> miette::Result<Vec<Game>, AocError> {
+    let parser = Parser {
+        input,
+        known: (unknown == UnknownColours::Reject).then_some(bag),
+    };
+
+    let mut declared = HashMap::new();
+    let lines = Spanned {
+        offset: 0,
+        text: input,
+    }
+    .split('\n')
+    .map(Spanned::trim)
+    .filter(|line| !line.text.is_empty());
+
+    let mut games = vec![];
+    for line in lines {
+        let (game, id) = parser.parse_game(line)?;
+
+        // Ids are what part 1 sums, so each game must have its own
+        if let Some(first) = declared.insert(game.id, id) {
+            return Err(AocError::DuplicateGameId {
+                id: game.id,
+                src: input.to_string(),
+                first: first.span(),
+                second: id.span(),
+            });
+        }
+        games.push(game);
+    }
+
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_games() -> miette::Result<()> {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+";
+        let games = parse_games(input)?;
+
+        assert_eq!(2, games.len());
+        assert_eq!(
+            Game {
+                id: 1,
+                rounds: vec![
+                    CubeSet::from([("blue", 3), ("red", 4)]),
+                    CubeSet::from([("red", 1), ("green", 2), ("blue", 6)]),
+                    CubeSet::from([("green", 2)]),
+                ],
+            },
+            games[0]
+        );
+        assert_eq!(
+            CubeSet::from([("red", 1), ("green", 3), ("blue", 4)]),
+            games[1].minimal_set()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            (
+                "Game 1: 3 blue\nGame x: 1 red",
+                "game id is not a number",
+                "x",
+            ),
+            (
+                "Game 1: 3 blue; four red",
+                "cube count is not a number",
+                "four",
+            ),
+            ("Game 1: 3 blue,, 1 red", "expected `<count> <colour>`", ""),
+            ("Round 1: 3 blue", "expected `Game <id>`", "Round 1"),
+            ("Game -1: 3 blue", "game id is not a number", "-1"),
+            ("Game +1: 3 blue", "game id is not a number", "+1"),
+            ("Game : 3 blue", "game id is not a number", ""),
+            (
+                "Game 1 3 blue",
+                "expected `Game <id>:` before the rounds",
+                "Game 1 3 blue",
+            ),
+            (
+                "Game 1: 3 dark blue",
+                "colour is not an identifier",
+                "dark blue",
+            ),
+            ("Game 1: +3 blue", "cube count is not a number", "+3"),
+            (
+                "Game 1: 4294967296 red",
+                "cube count is not a number",
+                "4294967296",
+            ),
+            (
+                "Game 1: 1 blue; 4294967295 red, 1 red",
+                "too many cubes of the colour in the round",
+                "1 red",
+            ),
+        ];
+
+        for (input, expected, text) in cases {
+            match parse_games(input) {
+                Err(AocError::InvalidGame { reason, span, .. }) => {
+                    assert_eq!(expected, reason);
+                    assert_eq!(text, &input[span.offset()..span.offset() + span.len()]);
+                }
+                other => panic!("{input:?} should not parse, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_bag() -> miette::Result<()> {
+        assert_eq!(
+            CubeSet::from([("red", 12), ("green", 13), ("blue", 14)]),
+            "red=12,green=13,blue=14".parse::<CubeSet>()?
+        );
+        assert_eq!(
+            CubeSet::from([("purple", 3), ("red", 1)]),
+            "# bag of the variant\npurple = 3\n\nred = 1\n".parse::<CubeSet>()?
+        );
+
+        match "red=12,light purple=1".parse::<CubeSet>() {
+            Err(AocError::InvalidBag { span, .. }) => {
+                assert_eq!((7, 12), (span.offset(), span.len()))
+            }
+            other => panic!("invalid colour should be rejected, got {other:?}"),
+        }
+        assert!("red=+3".parse::<CubeSet>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_colours() -> miette::Result<()> {
+        let input = "Game 1: 2 purple, 1 red; 3 red\nGame 2: 1 teal";
+        let bag = CubeSet::from([("red", 3), ("purple", 2)]);
+
+        let games = parse_games_with(input, &bag, UnknownColours::Allow)?;
+        assert!(games[0].is_possible_with(&bag));
+        assert!(!games[1].is_possible_with(&bag));
+        assert_eq!(
+            CubeSet::from([("purple", 2), ("red", 3)]),
+            games[0].minimal_set()
+        );
+
+        match parse_games_with(input, &bag, UnknownColours::Reject) {
+            Err(AocError::UnknownColour { colour, span, .. }) => {
+                assert_eq!("teal", colour);
+                assert_eq!("teal", &input[span.offset()..span.offset() + span.len()]);
+            }
+            other => panic!("teal should be rejected, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_ids() {
+        let input = "Game 4: 3 blue\nGame 12: 1 red\nGame 4: 2 green";
+
+        match parse_games(input) {
+            Err(AocError::DuplicateGameId {
+                id, first, second, ..
+            }) => {
+                assert_eq!(4, id);
+                assert_eq!((5, 1), (first.offset(), first.len()));
+                assert_eq!((35, 1), (second.offset(), second.len()));
+            }
+            other => panic!("game 4 is declared twice, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    custom_error::AocError,
+    game::{colours_of, CubeSet, Game},
+};
+
+// The fewest cubes of each colour a bag must hold for every game of `possible` to be possible,
+// i.e. the largest minimal set of those games. Every colour of the log is listed.
+fn lower_bound(games: &[Game], possible: &BTreeSet<u32>) -> miette::Result<CubeSet, AocError> {
+    if let Some(id) = possible
+        .iter()
+        .find(|id| !games.iter().any(|game| game.id == **id))
+    {
+        return Err(AocError::UnknownGameId(*id));
+    }
+
+    let mut bound = CubeSet::default();
+    for colour in colours_of(games) {
+        bound.set(&colour, 0);
+    }
+    Ok(games
+        .iter()
+        .filter(|game| possible.contains(&game.id))
+        .fold(bound, |acc, game| acc.max(&game.minimal_set())))
+}
+
+// Tells whether the games possible with the bag are exactly those of `possible`
+fn is_consistent(games: &[Game], possible: &BTreeSet<u32>, bag: &CubeSet) -> bool {
+    games
+        .iter()
+        .all(|game| game.is_possible_with(bag) == possible.contains(&game.id))
+}
+
+// Finds the bag with the fewest cubes making exactly the games of `possible` possible, if one
+// holds at most `budget` cubes. Adding cubes to a bag never makes a game impossible, so that bag
+// is the lower bound itself, or there is none.
+pub fn minimal_bag(
+    games: &[Game],
+    possible: &BTreeSet<u32>,
+    budget: u32,
+) -> miette::Result<Option<CubeSet>, AocError> {
+    let bag = lower_bound(games, possible)?;
+    let total = bag.iter().map(|(_, count)| count).sum::<u32>();
+
+    Ok((total <= budget && is_consistent(games, possible, &bag)).then_some(bag))
+}
+
+// Lists every bag of at most `budget` cubes, over the colours of the log, making exactly the
+// games of `possible` possible. There can be many of them for a large budget.
+pub fn infer_bags(
+    games: &[Game],
+    possible: &BTreeSet<u32>,
+    budget: u32,
+) -> miette::Result<Vec<CubeSet>, AocError> {
+    let bound = lower_bound(games, possible)?;
+    let mut bags = vec![];
+
+    // No bag is consistent if the lower bound already makes another game possible
+    if minimal_bag(games, possible, budget)?.is_some() {
+        let colours = bound.iter().collect::<Vec<(&str, u32)>>();
+        let minimum = colours.iter().map(|(_, count)| count).sum::<u32>();
+        enumerate(
+            games,
+            possible,
+            &colours,
+            budget - minimum,
+            bound.clone(),
+            &mut bags,
+        );
+    }
+    Ok(bags)
+}
+
+// Tries every way of spreading the `spare` cubes left over the colours still to be decided
+fn enumerate(
+    games: &[Game],
+    possible: &BTreeSet<u32>,
+    colours: &[(&str, u32)],
+    spare: u32,
+    bag: CubeSet,
+    bags: &mut Vec<CubeSet>,
+) {
+    let Some(((colour, minimum), rest)) = colours.split_first() else {
+        if is_consistent(games, possible, &bag) {
+            bags.push(bag);
+        }
+        return;
+    };
+
+    for extra in 0..=spare {
+        let mut bag = bag.clone();
+        bag.set(colour, minimum + extra);
+        enumerate(games, possible, rest, spare - extra, bag, bags);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::parse_games, part1::puzzle_bag};
+
+    const INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn test_minimal_bag() -> miette::Result<()> {
+        let games = parse_games(INPUT)?;
+
+        assert_eq!(
+            Some(CubeSet::from([("red", 6), ("green", 3), ("blue", 6)])),
+            minimal_bag(&games, &BTreeSet::from([1, 2, 5]), 39)?
+        );
+        assert_eq!(None, minimal_bag(&games, &BTreeSet::from([1, 2, 5]), 14)?);
+
+        // A bag holding enough cubes for games 1 and 5 always holds enough for game 2
+        assert_eq!(None, minimal_bag(&games, &BTreeSet::from([1, 5]), 39)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_infer_bags() -> miette::Result<()> {
+        let games = parse_games(INPUT)?;
+        let possible = BTreeSet::from([1, 2, 5]);
+
+        let bags = infer_bags(&games, &possible, 39)?;
+        assert!(bags.contains(&puzzle_bag()));
+        assert!(bags.contains(&CubeSet::from([("red", 6), ("green", 3), ("blue", 6)])));
+        assert!(bags.iter().all(|bag| {
+            bag.iter().map(|(_, count)| count).sum::<u32>() <= 39
+                && games
+                    .iter()
+                    .all(|game| game.is_possible_with(bag) == possible.contains(&game.id))
+        }));
+
+        // One spare cube can go to any colour, and none of them makes game 3 or 4 possible
+        assert_eq!(4, infer_bags(&games, &possible, 16)?.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_game_id() -> miette::Result<()> {
+        let games = parse_games(INPUT)?;
+        assert!(matches!(
+            infer_bags(&games, &BTreeSet::from([1, 6]), 39),
+            Err(AocError::UnknownGameId(6))
+        ));
+        Ok(())
+    }
+}
@@ -1,4 +1,8 @@
 pub mod custom_error;
+pub mod export;
+pub mod game;
+pub mod infer;
+pub mod report;
 
 pub mod part1;
 pub mod part2;
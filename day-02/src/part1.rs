@@ -1,58 +1,38 @@
-use crate::custom_error::AocError;
+use crate::{
+    custom_error::AocError,
+    game::{parse_games_with, CubeSet, Game, UnknownColours},
+};
 
-const AVAILABLE_RED_CUBES: u16 = 12;
-const AVAILABLE_GREEN_CUBES: u16 = 13;
-const AVAILABLE_BLUE_CUBES: u16 = 14;
+// The bag of the puzzle
+pub fn puzzle_bag() -> CubeSet {
+    CubeSet::from([("red", 12), ("green", 13), ("blue", 14)])
+}
 
 pub fn process(input: &str) -> miette::Result<String, AocError> {
-    let x: u16 = input
-        .lines()
-        .into_iter()
-        .map(|line| {
-            let false_number = line.trim().split(':').collect::<Vec<&str>>()[1]
-                .split(';')
-                .map(|sub_game| {
-                    let mut current_red = 0;
-                    let mut current_green = 0;
-                    let mut current_blue = 0;
-
-                    for value in sub_game.trim().split(',') {
-                        match value
-                            .trim()
-                            .split_whitespace()
-                            .collect::<Vec<&str>>()
-                            .as_slice()
-                        {
-                            [x, "red"] => current_red = current_red + x.parse::<u16>().unwrap(),
-                            [x, "green"] => {
-                                current_green = current_green + x.parse::<u16>().unwrap()
-                            }
-                            [x, "blue"] => current_blue = current_blue + x.parse::<u16>().unwrap(),
-                            _ => (),
-                        }
-                    }
-
-                    current_red <= AVAILABLE_RED_CUBES
-                        && current_green <= AVAILABLE_GREEN_CUBES
-                        && current_blue <= AVAILABLE_BLUE_CUBES
-                })
-                .collect::<Vec<bool>>()
-                .into_iter()
-                .filter(|x| !x)
-                .count();
+    process_with(input, &puzzle_bag(), UnknownColours::Allow)
+}
 
-            false_number != 0
-        })
-        .enumerate()
-        .filter(|x| !x.1)
-        .map(|x| (x.0 + 1) as u16)
-        .collect::<Vec<u16>>()
-        .into_iter()
-        .sum();
+// Sums the ids of the games that could have been played with the given bag
+pub fn process_with(
+    input: &str,
+    bag: &CubeSet,
+    unknown: UnknownColours,
+) -> miette::Result<String, AocError> {
+    let x = solve(&parse_games_with(input, bag, unknown)?, bag);
 
     Ok(x.to_string())
 }
 
+// Sums the ids of the games, however they were loaded, that are possible with the bag. Ids are
+// distinct 32-bit numbers, so their sum always fits in 64 bits.
+pub fn solve(games: &[Game], bag: &CubeSet) -> u64 {
+    games
+        .iter()
+        .filter(|game| game.is_possible_with(bag))
+        .map(|game| game.id as u64)
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,4 +47,45 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
         assert_eq!("8", process(input)?);
         Ok(())
     }
+
+    #[test]
+    fn test_process_with() -> miette::Result<()> {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        let bag = "red=20,green=13,blue=15".parse()?;
+        assert_eq!("15", process_with(input, &bag, UnknownColours::Allow)?);
+        let bag = "red=1,green=3,blue=4".parse()?;
+        assert_eq!("2", process_with(input, &bag, UnknownColours::Allow)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_arbitrary_colours() -> miette::Result<()> {
+        let input = "Game 1: 3 teal, 4 red; 1 red, 2 ochre
+Game 2: 1 teal, 2 purple
+Game 3: 5 teal, 1 ochre";
+        let bag = "teal=5,red=4,ochre=2".parse()?;
+        assert_eq!("4", process_with(input, &bag, UnknownColours::Allow)?);
+        assert!(process_with(input, &bag, UnknownColours::Reject).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_declared_ids() -> miette::Result<()> {
+        // Games are filtered, reordered and numbered sparsely, so positions are not ids
+        let input = "Game 40: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 7: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 3: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 1000: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        assert_eq!("1043", process(input)?);
+        assert!(process("Game 1: 1 red\nGame 1: 2 red").is_err());
+        assert_eq!(
+            "8589934589",
+            process("Game 4294967295: 1 red\nGame 4294967294: 1 red")?
+        );
+        Ok(())
+    }
 }
@@ -1,48 +1,42 @@
-use rayon::{
-    iter::{IntoParallelIterator, ParallelIterator},
-    str::ParallelString,
-};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
-use crate::custom_error::AocError;
+use crate::{
+    custom_error::AocError,
+    game::{colours_of, parse_games, Game},
+};
 
 pub fn process(input: &str) -> miette::Result<String, AocError> {
-    let x: u32 = input
-        .par_lines()
-        .into_par_iter()
-        .map(|line| {
-            let mut reds_amount = vec![];
-            let mut greens_amount = vec![];
-            let mut blues_amount = vec![];
-
-            let sub_games = line.trim().split(':').collect::<Vec<&str>>()[1].split(';');
-
-            for game in sub_games {
-                for value in game.trim().split(',') {
-                    match value
-                        .trim()
-                        .split_whitespace()
-                        .collect::<Vec<&str>>()
-                        .as_slice()
-                    {
-                        [x, "red"] => reds_amount.push(x.parse::<u32>().unwrap()),
-                        [x, "green"] => greens_amount.push(x.parse::<u32>().unwrap()),
-                        [x, "blue"] => blues_amount.push(x.parse::<u32>().unwrap()),
-                        _ => (),
-                    }
-                }
-            }
-
-            reds_amount.into_iter().max().unwrap()
-                * greens_amount.into_iter().max().unwrap()
-                * blues_amount.into_iter().max().unwrap()
-        })
-        .collect::<Vec<u32>>()
-        .into_par_iter()
-        .sum();
+    let x = solve(&parse_games(input)?)?;
 
     Ok(x.to_string())
 }
 
+// Sums the powers of the minimal sets of the games, however they were loaded
+pub fn solve(games: &[Game]) -> miette::Result<u64, AocError> {
+    // The power of a set is taken over every colour of the log, a game that never shows one of
+    // them having a power of zero
+    let colours = colours_of(games);
+
+    // Powers are computed in parallel but summed in order, so that the first overflow is the one
+    // reported
+    games
+        .par_iter()
+        .map(|game| {
+            game.minimal_set()
+                .power(colours.iter().map(String::as_str))
+                .ok_or_else(|| AocError::Overflow {
+                    what: format!("the power of game {}", game.id),
+                })
+        })
+        .collect::<Vec<Result<u64, AocError>>>()
+        .into_iter()
+        .try_fold(0_u64, |sum, power| {
+            sum.checked_add(power?).ok_or_else(|| AocError::Overflow {
+                what: "the sum of the powers".to_string(),
+            })
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -57,4 +51,53 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
         assert_eq!("2286", process(input)?);
         Ok(())
     }
+
+    #[test]
+    fn test_missing_colour() -> miette::Result<()> {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 6 blue
+Game 2: 1 blue, 2 green, 3 red";
+        assert_eq!("6", process(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_arbitrary_colours() -> miette::Result<()> {
+        let input = "Game 1: 3 teal, 4 red; 1 red, 2 ochre
+Game 2: 1 teal, 2 ochre, 5 red; 2 teal";
+        assert_eq!("44", process(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_declared_ids() -> miette::Result<()> {
+        let input = "Game 40: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 3: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue";
+        assert_eq!("60", process(input)?);
+        assert!(process("Game 2: 1 red\nGame two: 2 red").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wide_powers() -> miette::Result<()> {
+        // Powers are taken over every colour of the log, so they outgrow 32 bits quickly
+        let input = "Game 1: 100000 a, 100000 b; 3 c
+Game 2: 2 a, 1 b, 1 c";
+        assert_eq!("30000000002", process(input)?);
+        assert_eq!(
+            "18446744065119617025",
+            process("Game 1: 4294967295 a, 4294967295 b")?
+        );
+
+        let input = "Game 1: 100000 a, 100000 b, 100000 c, 100000 d";
+        assert!(matches!(
+            process(input),
+            Err(AocError::Overflow { what }) if what == "the power of game 1"
+        ));
+        let input = "Game 1: 4294967295 a, 4294967295 b\nGame 2: 4294967295 a, 4294967295 b";
+        assert!(matches!(
+            process(input),
+            Err(AocError::Overflow { what }) if what == "the sum of the powers"
+        ));
+        Ok(())
+    }
 }
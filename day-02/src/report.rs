@@ -0,0 +1,242 @@
+use std::fmt;
+
+use crate::{
+    custom_error::AocError,
+    game::{colours_of, CubeSet, Game},
+};
+
+// A colour a round shows more cubes of than the bag holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Excess {
+    pub colour: String,
+    pub shown: u32,
+    pub available: u32,
+}
+
+impl Excess {
+    // Gives back how many cubes are missing from the bag for the round to be possible
+    pub fn amount(&self) -> u32 {
+        self.shown - self.available
+    }
+}
+
+// A round that could not have been drawn from the bag, `index` starting at 0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundViolation {
+    pub index: usize,
+    pub excesses: Vec<Excess>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfeasibleGame {
+    pub id: u32,
+    pub rounds: Vec<RoundViolation>,
+}
+
+// The games of a log that could not have been played with a bag, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeasibilityReport {
+    pub bag: CubeSet,
+    pub infeasible: Vec<InfeasibleGame>,
+}
+
+// Finds, for every game impossible with the bag, the rounds and colours exceeding it
+pub fn feasibility_report(games: &[Game], bag: &CubeSet) -> FeasibilityReport {
+    let infeasible = games
+        .iter()
+        .filter(|game| !game.is_possible_with(bag))
+        .map(|game| InfeasibleGame {
+            id: game.id,
+            rounds: game
+                .rounds
+                .iter()
+                .enumerate()
+                .filter(|(_, round)| !round.fits_in(bag))
+                .map(|(index, round)| RoundViolation {
+                    index,
+                    excesses: round
+                        .iter()
+                        .filter(|(colour, count)| *count > bag.get(colour))
+                        .map(|(colour, count)| Excess {
+                            colour: colour.to_string(),
+                            shown: count,
+                            available: bag.get(colour),
+                        })
+                        .collect(),
+                })
+                .collect(),
+        })
+        .collect();
+
+    FeasibilityReport {
+        bag: bag.clone(),
+        infeasible,
+    }
+}
+
+// Prints each impossible game followed by its offending rounds, e.g.
+//     Game 3 is impossible:
+//       round 1: 20 red shown, 12 available (8 too many)
+impl fmt::Display for FeasibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} impossible game(s) with a bag of {}",
+            self.infeasible.len(),
+            self.bag
+        )?;
+        for game in self.infeasible.iter() {
+            writeln!(f, "Game {} is impossible:", game.id)?;
+            for round in game.rounds.iter() {
+                for excess in round.excesses.iter() {
+                    writeln!(
+                        f,
+                        "  round {}: {} {} shown, {} available ({} too many)",
+                        round.index + 1,
+                        excess.shown,
+                        excess.colour,
+                        excess.available,
+                        excess.amount()
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinimalSet {
+    pub id: u32,
+    pub set: CubeSet,
+    pub power: u64,
+}
+
+// The minimal set of every game of a log, over every colour the log shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinimalSetTable {
+    pub colours: Vec<String>,
+    pub rows: Vec<MinimalSet>,
+}
+
+pub fn minimal_set_table(games: &[Game]) -> miette::Result<MinimalSetTable, AocError> {
+    let colours = colours_of(games);
+    let rows = games
+        .iter()
+        .map(|game| {
+            let set = game.minimal_set();
+            let power = set
+                .power(colours.iter().map(String::as_str))
+                .ok_or_else(|| AocError::Overflow {
+                    what: format!("the power of game {}", game.id),
+                })?;
+            Ok(MinimalSet {
+                id: game.id,
+                power,
+                set,
+            })
+        })
+        .collect::<Result<Vec<MinimalSet>, AocError>>()?;
+
+    Ok(MinimalSetTable { colours, rows })
+}
+
+// Prints one row per game and one column per colour, each column as wide as its header
+impl fmt::Display for MinimalSetTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut header = format!("{:>6}", "game");
+        for colour in self.colours.iter() {
+            header += &format!(" | {colour:>5}");
+        }
+        writeln!(f, "{header} | {:>8}", "power")?;
+
+        for row in self.rows.iter() {
+            let mut line = format!("{:>6}", row.id);
+            for colour in self.colours.iter() {
+                line += &format!(
+                    " | {:>width$}",
+                    row.set.get(colour),
+                    width = colour.len().max(5)
+                );
+            }
+            writeln!(f, "{line} | {:>8}", row.power)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::parse_games, part1::puzzle_bag};
+
+    const INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn test_feasibility_report() -> miette::Result<()> {
+        let report = feasibility_report(&parse_games(INPUT)?, &puzzle_bag());
+
+        assert_eq!(
+            vec![3, 4],
+            report
+                .infeasible
+                .iter()
+                .map(|game| game.id)
+                .collect::<Vec<u32>>()
+        );
+        assert_eq!(
+            vec![RoundViolation {
+                index: 2,
+                excesses: vec![
+                    Excess {
+                        colour: "blue".to_string(),
+                        shown: 15,
+                        available: 14,
+                    },
+                    Excess {
+                        colour: "red".to_string(),
+                        shown: 14,
+                        available: 12,
+                    },
+                ],
+            }],
+            report.infeasible[1].rounds
+        );
+        assert_eq!(
+            "2 impossible game(s) with a bag of blue=14, green=13, red=12
+Game 3 is impossible:
+  round 1: 20 red shown, 12 available (8 too many)
+Game 4 is impossible:
+  round 3: 15 blue shown, 14 available (1 too many)
+  round 3: 14 red shown, 12 available (2 too many)
+",
+            report.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_minimal_set_table() -> miette::Result<()> {
+        let table = minimal_set_table(&parse_games(INPUT)?)?;
+
+        assert_eq!(
+            vec![48, 12, 1560, 630, 36],
+            table.rows.iter().map(|row| row.power).collect::<Vec<u64>>()
+        );
+        assert_eq!(
+            "  game |  blue | green |   red |    power
+     1 |     6 |     2 |     4 |       48
+     2 |     4 |     3 |     1 |       12
+     3 |     6 |    13 |    20 |     1560
+     4 |    15 |     3 |    14 |      630
+     5 |     2 |     3 |     6 |       36
+",
+            table.to_string()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, SamplingMode};
+use day_03::{
+    grid::Position,
+    schematic::{parse_schematic, part_numbers, Cell, SymbolIndex},
+};
+
+// Generates a square schematic of the given size, the same for every run
+fn generate(size: usize) -> String {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| match next() % 20 {
+                    0..=5 => char::from(b'0' + (next() % 10) as u8),
+                    6 => ['*', '#', '$', '+', '/', '=', '@', '%', '&', '-'][(next() % 10) as usize],
+                    _ => '.',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Part 1 as it used to be, scanning every symbol position for every digit
+fn linear(input: &str) -> u64 {
+    let grid = parse_schematic(input).unwrap();
+    let symbols = grid
+        .iter()
+        .filter(|(_, cell)| matches!(cell, Cell::Symbol(_)))
+        .map(|(position, _)| position)
+        .collect::<Vec<Position>>();
+
+    part_numbers(&grid)
+        .unwrap()
+        .iter()
+        .filter(|number| {
+            (number.col_start..number.col_end).any(|column| {
+                grid.neighbours8((number.row, column))
+                    .any(|neighbour| symbols.contains(&neighbour))
+            })
+        })
+        .map(|number| number.value)
+        .sum()
+}
+
+fn indexed(input: &str) -> u64 {
+    let grid = parse_schematic(input).unwrap();
+    let symbols = SymbolIndex::new(&grid, |cell| matches!(cell, Cell::Symbol(_)));
+
+    part_numbers(&grid)
+        .unwrap()
+        .iter()
+        .filter(|number| symbols.touches(number))
+        .map(|number| number.value)
+        .sum()
+}
+
+fn adjacency(c: &mut Criterion) {
+    let mut group = c.benchmark_group("adjacency");
+    // The linear scan takes tens of seconds a run on the largest schematic, so every benchmark
+    // takes the fewest samples criterion allows, each of the same number of iterations
+    group
+        .sample_size(10)
+        .sampling_mode(SamplingMode::Flat)
+        .warm_up_time(Duration::from_secs(1));
+
+    for size in [100, 300, 1000] {
+        let input = generate(size);
+        assert_eq!(
+            day_03::part1::process(&input).unwrap(),
+            indexed(&input).to_string()
+        );
+        assert_eq!(linear(&input), indexed(&input));
+
+        group.bench_with_input(BenchmarkId::new("linear", size), &input, |b, input| {
+            b.iter(|| linear(black_box(input)))
+        });
+        group.bench_with_input(BenchmarkId::new("indexed", size), &input, |b, input| {
+            b.iter(|| indexed(black_box(input)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, adjacency);
+criterion_main!(benches);
@@ -1,10 +1,70 @@
-use day_03::part1::process;
-use miette::Context;
+use std::{fs::File, io::BufReader};
+
+use day_03::{
+    part1::{process_par, process_reader},
+    report::symbol_report,
+    schematic::{parse_schematic_with, Layout, Neighbourhood, SymbolClass},
+};
+use miette::{Context, IntoDiagnostic};
+
+// Rows checked in parallel at once when the whole schematic is in memory
+const BAND_ROWS: usize = 16;
 
 fn main() -> miette::Result<()> {
-    let file = include_str!("../../input1.txt");
+    // `--symbols <characters>` and `--empty <characters>` change which characters are symbols
+    // and which are empty, and `--neighbourhood <4|8>` which cells surround a number
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let value_of = |name: &str| {
+        let index = args.iter().position(|arg| arg == name)?;
+        args.get(index + 1)
+    };
+    let mut layout = Layout::default();
+    if let Some(symbols) = value_of("--symbols") {
+        layout.classes.symbols = SymbolClass::Only(symbols.chars().collect());
+    }
+    if let Some(empty) = value_of("--empty") {
+        layout.classes.empty = empty.chars().collect();
+    }
+    if let Some(neighbourhood) = value_of("--neighbourhood") {
+        layout.neighbourhood = neighbourhood.parse::<Neighbourhood>()?;
+    }
+    let path = args
+        .iter()
+        .enumerate()
+        .find(|(index, arg)| {
+            let is_value = *index > 0
+                && ["--symbols", "--empty", "--neighbourhood"].contains(&&*args[index - 1]);
+            !arg.starts_with("--") && !is_value
+        })
+        .map(|(_, arg)| arg);
+
+    // `--report` lists the numbers around every symbol, their totals per symbol character and
+    // the numbers next to no symbol. It needs the whole schematic, so a path given as argument is
+    // loaded in that case
+    if args.iter().any(|arg| arg == "--report") {
+        let file = match path {
+            Some(path) => std::fs::read_to_string(path)
+                .into_diagnostic()
+                .with_context(|| format!("read {path}"))?,
+            None => include_str!("../../input1.txt").to_string(),
+        };
+        let grid = parse_schematic_with(&file, &layout.classes).context("parse part 1")?;
+        print!("{}", symbol_report(&grid, layout.neighbourhood)?);
+    }
 
-    let result = process(file).context("process part 1")?;
+    // A path given as argument is streamed instead of loading the bundled input
+    let result = match path {
+        Some(path) => {
+            let file = File::open(path)
+                .into_diagnostic()
+                .with_context(|| format!("open {path}"))?;
+            process_reader(BufReader::new(file), &layout).context("process part 1")?
+        }
+        None => {
+            let file = include_str!("../../input1.txt");
+            process_par(file, &layout, BAND_ROWS).context("process part 1")?
+        }
+    };
     println!("{}", result);
 
     Ok(())
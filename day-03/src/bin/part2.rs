@@ -1,10 +1,81 @@
-use day_03::part2::process;
-use miette::Context;
+use std::{fs::File, io::BufReader};
+
+use day_03::{
+    part2::{process_par, process_reader, Combine, GearRule},
+    schematic::{Layout, Neighbourhood, SymbolClass},
+};
+use miette::{Context, IntoDiagnostic};
+
+// Rows checked in parallel at once when the whole schematic is in memory
+const BAND_ROWS: usize = 16;
 
 fn main() -> miette::Result<()> {
-    let file = include_str!("../../input2.txt");
+    // The gear rule defaults to the puzzle's, and `--gears <symbols>`, `--neighbours <count>` and
+    // `--combine <product|sum>` change which symbols are gears, how many numbers they must touch
+    // and how these make up their ratio
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let value_of = |name: &str| {
+        let index = args.iter().position(|arg| arg == name)?;
+        args.get(index + 1)
+    };
+    let mut rule = GearRule::default();
+    let mut layout = Layout::default();
+    if let Some(symbols) = value_of("--gears") {
+        rule.symbols = symbols.chars().collect();
+    }
+    if let Some(neighbours) = value_of("--neighbours") {
+        rule.neighbours = neighbours
+            .parse()
+            .into_diagnostic()
+            .context("parse --neighbours")?;
+    }
+    if let Some(combine) = value_of("--combine") {
+        rule.combine = combine.parse::<Combine>()?;
+    }
+
+    // `--symbols <characters>`, `--empty <characters>` and `--neighbourhood <4|8>` change how the
+    // schematic is read, as for part 1
+    if let Some(symbols) = value_of("--symbols") {
+        layout.classes.symbols = SymbolClass::Only(symbols.chars().collect());
+    }
+    if let Some(empty) = value_of("--empty") {
+        layout.classes.empty = empty.chars().collect();
+    }
+    if let Some(neighbourhood) = value_of("--neighbourhood") {
+        layout.neighbourhood = neighbourhood.parse::<Neighbourhood>()?;
+    }
+
+    let path = args
+        .iter()
+        .enumerate()
+        .find(|(index, arg)| {
+            let is_value = *index > 0
+                && [
+                    "--gears",
+                    "--neighbours",
+                    "--combine",
+                    "--symbols",
+                    "--empty",
+                    "--neighbourhood",
+                ]
+                .contains(&&*args[index - 1]);
+            !arg.starts_with("--") && !is_value
+        })
+        .map(|(_, arg)| arg);
 
-    let result = process(file).context("process part 2")?;
+    // A path given as argument is streamed instead of loading the bundled input
+    let result = match path {
+        Some(path) => {
+            let file = File::open(path)
+                .into_diagnostic()
+                .with_context(|| format!("open {path}"))?;
+            process_reader(BufReader::new(file), &layout, &rule).context("process part 2")?
+        }
+        None => {
+            let file = include_str!("../../input2.txt");
+            process_par(file, &layout, &rule, BAND_ROWS).context("process part 2")?
+        }
+    };
     println!("{}", result);
 
     Ok(())
@@ -6,4 +6,24 @@ pub enum AocError {
     #[error(transparent)]
     #[diagnostic(code(aoc::io_error))]
     IoError(#[from] std::io::Error),
+
+    #[error("row {row} is {found} cells wide instead of {expected}")]
+    #[diagnostic(code(aoc::ragged_grid))]
+    RaggedGrid {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("unknown combining operation `{0}`")]
+    #[diagnostic(code(aoc::unknown_combine), help("expected one of `product` or `sum`"))]
+    UnknownCombine(String),
+
+    #[error("unknown neighbourhood `{0}`")]
+    #[diagnostic(code(aoc::unknown_neighbourhood), help("expected one of `4` or `8`"))]
+    UnknownNeighbourhood(String),
+
+    #[error("{what} does not fit in 64 bits")]
+    #[diagnostic(code(aoc::overflow))]
+    Overflow { what: String },
 }
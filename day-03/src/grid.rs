@@ -0,0 +1,197 @@
+use crate::custom_error::AocError;
+
+// A cell of a grid, as (row, column)
+pub type Position = (usize, usize);
+
+const NEIGHBOURS_4: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+// A rectangular grid of cells stored row after row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    // Builds a grid off of its rows, which must all have the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> miette::Result<Grid<T>, AocError> {
+        let width = rows.first().map_or(0, Vec::len);
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+
+        for (index, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(AocError::RaggedGrid {
+                    row: index + 1,
+                    expected: width,
+                    found: row.len(),
+                });
+            }
+            cells.extend(row);
+        }
+
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    // Builds a grid off of text, one row per line, turning every character into a cell
+    pub fn parse(input: &str, mut map: impl FnMut(char) -> T) -> miette::Result<Grid<T>, AocError> {
+        Grid::from_rows(
+            input
+                .lines()
+                .map(|line| line.chars().map(&mut map).collect())
+                .collect(),
+        )
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, (row, column): Position) -> Option<&T> {
+        (row < self.height && column < self.width).then(|| &self.cells[row * self.width + column])
+    }
+
+    pub fn get_mut(&mut self, (row, column): Position) -> Option<&mut T> {
+        (row < self.height && column < self.width)
+            .then(|| &mut self.cells[row * self.width + column])
+    }
+
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        (row < self.height).then(|| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|row| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    pub fn column(&self, column: usize) -> impl Iterator<Item = &T> {
+        let cells = if column < self.width {
+            &self.cells[column..]
+        } else {
+            &[]
+        };
+        cells.iter().step_by(self.width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|column| self.column(column))
+    }
+
+    // Iterates over every cell along with its position, row after row
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| ((index / self.width, index % self.width), cell))
+    }
+
+    // Builds a grid of the same size, mapping every cell
+    pub fn map<U>(&self, map: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(map).collect(),
+        }
+    }
+
+    fn offset(&self, (row, column): Position, (dr, dc): (isize, isize)) -> Option<Position> {
+        let row = row.checked_add_signed(dr)?;
+        let column = column.checked_add_signed(dc)?;
+        (row < self.height && column < self.width).then_some((row, column))
+    }
+
+    // Iterates over the cells sharing a side with the given one, leaving out those outside of
+    // the grid
+    pub fn neighbours4(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        NEIGHBOURS_4
+            .iter()
+            .filter_map(move |offset| self.offset(position, *offset))
+    }
+
+    // Iterates over the cells sharing a side or a corner with the given one, leaving out those
+    // outside of the grid
+    pub fn neighbours8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        NEIGHBOURS_8
+            .iter()
+            .filter_map(move |offset| self.offset(position, *offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() -> miette::Result<()> {
+        let grid = Grid::parse("abc\ndef", |character| character)?;
+
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(Some(&'f'), grid.get((1, 2)));
+        assert_eq!(None, grid.get((2, 0)));
+        assert_eq!(None, grid.get((0, 3)));
+        assert_eq!(Some(&['d', 'e', 'f'][..]), grid.row(1));
+        assert_eq!(
+            vec!['b', 'e'],
+            grid.column(1).copied().collect::<Vec<char>>()
+        );
+        assert_eq!(
+            vec!["ad", "be", "cf"],
+            grid.columns()
+                .map(|column| column.collect::<String>())
+                .collect::<Vec<String>>()
+        );
+        assert_eq!(((1, 0), &'d'), grid.iter().nth(3).unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn test_neighbours() -> miette::Result<()> {
+        let grid = Grid::parse("...\n...\n...", |character| character)?;
+
+        assert_eq!(
+            vec![(0, 1), (1, 0)],
+            grid.neighbours4((0, 0)).collect::<Vec<Position>>()
+        );
+        assert_eq!(
+            vec![(0, 1), (1, 0), (1, 1)],
+            grid.neighbours8((0, 0)).collect::<Vec<Position>>()
+        );
+        assert_eq!(8, grid.neighbours8((1, 1)).count());
+        assert_eq!(
+            vec![(1, 1), (1, 2), (2, 1)],
+            grid.neighbours8((2, 2)).collect::<Vec<Position>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ragged_rows() {
+        assert!(matches!(
+            Grid::parse("abc\nde", |character| character),
+            Err(AocError::RaggedGrid {
+                row: 2,
+                expected: 3,
+                found: 2
+            })
+        ));
+    }
+}
@@ -1,4 +1,9 @@
 pub mod custom_error;
+pub mod grid;
+pub mod query;
+pub mod report;
+pub mod schematic;
+pub mod stream;
 
 pub mod part1;
 pub mod part2;
@@ -1,176 +1,149 @@
-use itertools::Itertools;
+use std::io::BufRead;
 
-use crate::custom_error::AocError;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
-#[derive(Debug, Clone)]
-enum ValueType {
-    Number(u8),
-    Empty,
-    Symbol,
-}
+use crate::{
+    custom_error::AocError,
+    schematic::{parse_schematic_with, part_numbers, row_numbers, Cell, Layout, SymbolIndex},
+    stream::for_each_window,
+};
 
-#[derive(Debug, Clone)]
-struct CharType {
-    value: ValueType,
-    keep_group: bool,
-    coordinates: (usize, usize),
+fn add_part(res: u64, value: u64) -> miette::Result<u64, AocError> {
+    res.checked_add(value).ok_or_else(|| AocError::Overflow {
+        what: "the sum of the part numbers".to_string(),
+    })
 }
 
-// Constructs a CharType off of a character and its coordinates in the grid
-fn filter_value(x: usize, y: usize, character: &char) -> CharType {
-    CharType {
-        coordinates: (x, y),
-        value: match character {
-            c if c.is_digit(10) => ValueType::Number(
-                character
-                    .to_string()
-                    .parse::<u8>()
-                    .expect("Should be a number"),
-            ),
-            '.' => ValueType::Empty,
-            _ => ValueType::Symbol,
-        },
-        keep_group: false,
-    }
-}
-
-// Computes if a character has a symbol in its surrounding
-fn filter_keepable(character: &CharType, symbol_vec: &Vec<(usize, usize)>) -> CharType {
-    let x = character.coordinates.0 as i32;
-    let y = character.coordinates.1 as i32;
-
-    let window = vec![
-        (x, y + 1),
-        (x, y - 1),
-        (x + 1, y),
-        (x - 1, y),
-        (x + 1, y + 1),
-        (x + 1, y - 1),
-        (x - 1, y + 1),
-        (x - 1, y - 1),
-    ];
-
-    for (x, y) in window {
-        if symbol_vec.contains(&(x as usize, y as usize)) {
-            return CharType {
-                value: character.value.clone(),
-                coordinates: character.coordinates,
-                keep_group: true,
-            };
-        }
-    }
-    character.clone()
+pub fn process(input: &str) -> miette::Result<String, AocError> {
+    process_with(input, &Layout::default())
 }
 
-// gives back true if any number in the integer chain is true
-fn do_keep_integer(integer: &Vec<&CharType>) -> bool {
-    integer
-        .iter()
-        .fold(false, |acc, character| acc || character.keep_group)
-}
+// Sums the numbers surrounded by a symbol, reading the schematic with the given layout
+pub fn process_with(input: &str, layout: &Layout) -> miette::Result<String, AocError> {
+    let grid = parse_schematic_with(input, &layout.classes)?;
+    let symbols = SymbolIndex::with_neighbourhood(&grid, layout.neighbourhood, |cell| {
+        matches!(cell, Cell::Symbol(_))
+    });
 
-// Computes the value of a chain of integer
-fn group_integer(group: &Vec<&CharType>) -> u32 {
-    group
+    // Keeps the numbers with a symbol in their surrounding
+    let res = part_numbers(&grid)?
         .iter()
-        .rev()
-        .enumerate()
-        .fold(0, |acc, (index, character)| {
-            if let ValueType::Number(value) = character.value {
-                acc + (10 as u32).pow(index as u32) * value as u32
-            } else {
-                acc
-            }
-        })
-}
+        .filter(|number| symbols.touches(number))
+        .try_fold(0, |res, number| add_part(res, number.value))?;
 
-// Flattens groups of integers (a.k.a a line) and gives back their sum
-fn flatten_groups(groups: &Vec<Vec<&CharType>>) -> u32 {
-    let mut res = 0;
-    for group in groups.iter() {
-        if do_keep_integer(group) {
-            res = res + group_integer(group);
-        }
-    }
-    res
+    Ok(res.to_string())
 }
 
-pub fn process(input: &str) -> miette::Result<String, AocError> {
-    // Process the grid as a 2d vector of characters
-    let grid = input
-        .trim()
-        .lines()
-        .map(|line| {
-            line.as_bytes()
-                .iter()
-                .map(|x| char::from_u32(*x as u32).expect("Should be a char"))
-                .collect::<Vec<char>>()
+// Same as `process_with`, but checks bands of `band_rows` rows in parallel. Every number belongs
+// to the band of its row, and the symbols around it are looked up across bands. The sums of the
+// bands are added in order, so that the result does not depend on how the bands are scheduled.
+pub fn process_par(
+    input: &str,
+    layout: &Layout,
+    band_rows: usize,
+) -> miette::Result<String, AocError> {
+    let grid = parse_schematic_with(input, &layout.classes)?;
+    let symbols = SymbolIndex::with_neighbourhood(&grid, layout.neighbourhood, |cell| {
+        matches!(cell, Cell::Symbol(_))
+    });
+    let band_rows = band_rows.max(1);
+
+    let bands = (0..grid.height().div_ceil(band_rows))
+        .into_par_iter()
+        .map(|band| {
+            (band * band_rows..((band + 1) * band_rows).min(grid.height()))
+                .flat_map(|row| row_numbers(row, grid.row(row).unwrap_or(&[])))
+                .try_fold(0, |res, number| {
+                    let number = number?;
+                    if symbols.touches(&number) {
+                        add_part(res, number.value)
+                    } else {
+                        Ok(res)
+                    }
+                })
         })
-        .collect::<Vec<Vec<char>>>();
-
-    // Make that grid into a 2d vector of an easier to process custom type
-    let processed_characters = grid
-        .iter()
-        .enumerate()
-        .map(|(x, line)| {
-            line.iter()
-                .enumerate()
-                .map(|(y, character)| filter_value(x, y, character))
-                .collect::<Vec<CharType>>()
-        })
-        .collect::<Vec<Vec<CharType>>>();
-
-    // Flattens the 2d vector because coordinates are now encoded into a tuple linked to the value
-    // of the character
-    let characters = processed_characters
-        .iter()
-        .flatten()
-        .collect::<Vec<&CharType>>();
-
-    // Gets only the characters that are numbers
-    let numbers = characters
-        .iter()
-        .filter(|x| matches!(x.value, ValueType::Number(_)))
-        .collect::<Vec<_>>();
-
-    // Gets only the characters that are Symbols
-    let symbols = characters
-        .clone()
-        .iter()
-        .filter(|x| matches!(x.value, ValueType::Symbol))
-        .map(|x| x.coordinates)
-        .collect::<Vec<(usize, usize)>>();
+        .collect::<Vec<miette::Result<u64, AocError>>>();
 
-    // Processes the numbers to find out which one are next to symbols
-    let associated_numbers = numbers
+    let res = bands
         .into_iter()
-        .map(|number| filter_keepable(number, &symbols))
-        .collect::<Vec<CharType>>();
+        .try_fold(0, |res, band| add_part(res, band?))?;
 
+    Ok(res.to_string())
+}
+
+// Same as `process_with`, but reads the schematic row by row so that only the rows around the
+// numbers being checked are kept in memory
+pub fn process_reader(reader: impl BufRead, layout: &Layout) -> miette::Result<String, AocError> {
     let mut res = 0;
 
-    // Groups lines together
-    for (_, groups) in &associated_numbers
-        .into_iter()
-        .group_by(|elt| elt.coordinates.0)
-    {
-        let groups = groups.collect::<Vec<_>>();
-        // Groups contiguous strings of numbers together
-        let groups = (&(0..groups.len()).group_by(|&i| groups[i].coordinates.1 as usize - i))
-            .into_iter()
-            .map(|(_, group)| group.map(|i| &groups[i]).collect::<Vec<_>>())
-            .collect::<Vec<Vec<_>>>();
-
-        // Processes the value of each string of number in the line and sums them up
-        res = res + flatten_groups(&groups);
-    }
+    for_each_window(reader, &layout.classes, |window| {
+        let symbols =
+            SymbolIndex::with_neighbourhood(&window.cells, layout.neighbourhood, |cell| {
+                matches!(cell, Cell::Symbol(_))
+            });
+        for number in window.numbers(window.centre) {
+            let number = number?;
+            if symbols.touches(&number) {
+                res = add_part(res, number.value)?;
+            }
+        }
+        Ok(())
+    })?;
 
     Ok(res.to_string())
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+
     use super::*;
+    use crate::schematic::{CharClasses, Neighbourhood, SymbolClass};
+
+    const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    // Schematics on which every way of processing them must agree, edge cases included
+    const INPUTS: [&str; 12] = [
+        EXAMPLE,
+        "12..#.34\n.......$\n5......6\n#.....7.\n9.....10",
+        "#...\r\n.12.\r\n...$\r\n",
+        "1#2",
+        "",
+        "1..\n   \n#..",
+        "  1\n#..",
+        "\n\n1#.\n\n",
+        "1#.\n\n...",
+        "467..\n...*.\n  ",
+        " \t\n1#.\n \r\n\n",
+        "...\n..",
+    ];
+
+    // Layouts the schematics are processed with, one of them reading spaces as empty cells
+    fn layouts() -> [Layout; 3] {
+        [
+            Layout::default(),
+            Layout {
+                neighbourhood: Neighbourhood::Four,
+                ..Layout::default()
+            },
+            Layout {
+                classes: CharClasses {
+                    empty: BTreeSet::from(['.', ' ']),
+                    ..CharClasses::default()
+                },
+                ..Layout::default()
+            },
+        ]
+    }
 
     #[test]
     fn test_process() -> miette::Result<()> {
@@ -187,4 +160,88 @@ mod tests {
         assert_eq!("4361", process(input)?);
         Ok(())
     }
+
+    #[test]
+    fn test_numbers_on_edges() -> miette::Result<()> {
+        // Numbers in every corner and along every edge, next to a symbol or not
+        let input = "12..#.34
+.......$
+5......6
+#.....7.
+9.....10";
+        assert_eq!("54", process(input)?);
+        let input = "#...
+.12.
+...$";
+        assert_eq!("12", process(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_with() -> miette::Result<()> {
+        assert_eq!("4361", process(&EXAMPLE.replace('\n', "\r\n"))?);
+
+        let layout = Layout {
+            neighbourhood: Neighbourhood::Four,
+            ..Layout::default()
+        };
+        assert_eq!("2547", process_with(EXAMPLE, &layout)?);
+
+        let layout = Layout {
+            classes: CharClasses {
+                symbols: SymbolClass::Only(BTreeSet::from(['*'])),
+                ..CharClasses::default()
+            },
+            ..Layout::default()
+        };
+        assert_eq!("2472", process_with(EXAMPLE, &layout)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_reader() -> miette::Result<()> {
+        let layouts = layouts();
+
+        for input in INPUTS {
+            for layout in layouts.iter() {
+                assert_eq!(
+                    process_with(input, layout).map_err(|error| error.to_string()),
+                    process_reader(input.as_bytes(), layout).map_err(|error| error.to_string())
+                );
+            }
+        }
+
+        // Rows of spaces are rows like any other, only blank lines around the schematic are left out
+        assert_eq!("467", process_with("467..\n...*.\n  ", &layouts[0])?);
+        assert_eq!(
+            "467",
+            process_reader("467..\n...*.\n  ".as_bytes(), &layouts[0])?
+        );
+        assert_eq!(
+            "1",
+            process_reader("1..\n   \n#..".as_bytes(), &Layout::default())?
+        );
+        assert_eq!("0", process_reader("  1\n#..".as_bytes(), &layouts[2])?);
+        assert_eq!("0", process_with("  1\n#..", &layouts[2])?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_par() -> miette::Result<()> {
+        let layouts = layouts();
+
+        // Bands of every size, so that numbers and symbols fall on both sides of their boundaries
+        for input in INPUTS {
+            for layout in layouts.iter() {
+                let sequential = process_with(input, layout).map_err(|error| error.to_string());
+                for band_rows in 0..=11 {
+                    assert_eq!(
+                        sequential,
+                        process_par(input, layout, band_rows).map_err(|error| error.to_string())
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
 }
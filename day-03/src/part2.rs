@@ -1,205 +1,248 @@
-use std::usize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    io::BufRead,
+    str::FromStr,
+    sync::Arc,
+};
 
-use itertools::Itertools;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
-use crate::custom_error::AocError;
+use crate::{
+    custom_error::AocError,
+    grid::Position,
+    schematic::{parse_schematic_with, part_numbers, row_numbers, Cell, Layout, SymbolIndex},
+    stream::for_each_window,
+};
 
-#[derive(Debug, Clone)]
-enum ValueType {
-    Number(u8),
-    Empty,
-    Gear(((usize, usize), (usize, usize))),
+// A combining operation given by the caller, giving back `None` when the ratio overflows
+pub type CombineFn = dyn Fn(&[u64]) -> Option<u64> + Send + Sync;
+
+// How the numbers surrounding a gear are combined into its ratio
+#[derive(Clone, Default)]
+pub enum Combine {
+    #[default]
+    Product,
+    Sum,
+    Custom(Arc<CombineFn>),
 }
 
-#[derive(Debug, Clone)]
-struct GearPart {
-    value: u32,
-    gear: (usize, usize),
+impl Combine {
+    // Gives back the ratio of the values, or `None` if it does not fit in 64 bits
+    pub fn apply(&self, values: &[u64]) -> Option<u64> {
+        match self {
+            Combine::Product => values
+                .iter()
+                .try_fold(1_u64, |ratio, value| ratio.checked_mul(*value)),
+            Combine::Sum => values
+                .iter()
+                .try_fold(0_u64, |ratio, value| ratio.checked_add(*value)),
+            Combine::Custom(combine) => combine(values),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
-struct CharType {
-    value: ValueType,
-    coordinates: (usize, usize),
-    related_gear: (usize, usize),
+impl fmt::Debug for Combine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Combine::Product => write!(f, "Product"),
+            Combine::Sum => write!(f, "Sum"),
+            Combine::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
 }
 
-// Constructs a CharType off of a character and its coordinates in the grid
-fn filter_value(x: usize, y: usize, character: &char) -> CharType {
-    CharType {
-        coordinates: (x, y),
-        value: match character {
-            c if c.is_digit(10) => ValueType::Number(
-                character
-                    .to_string()
-                    .parse::<u8>()
-                    .expect("Should be a number"),
-            ),
-            '*' => ValueType::Gear(((0, 0), (0, 0))),
-            _ => ValueType::Empty,
-        },
-        related_gear: (0, 0),
+impl FromStr for Combine {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "product" => Ok(Combine::Product),
+            "sum" => Ok(Combine::Sum),
+            _ => Err(AocError::UnknownCombine(s.to_string())),
+        }
     }
 }
 
-// Computes if a character has a gear symbol in its surrounding
-fn filter_keepable(character: &CharType, symbol_vec: &Vec<(usize, usize)>) -> CharType {
-    let x = character.coordinates.0 as i32;
-    let y = character.coordinates.1 as i32;
-
-    let window = vec![
-        (x, y + 1),
-        (x, y - 1),
-        (x + 1, y),
-        (x - 1, y),
-        (x + 1, y + 1),
-        (x + 1, y - 1),
-        (x - 1, y + 1),
-        (x - 1, y - 1),
-    ];
+// Which symbols are gears, how many numbers they must be surrounded by and how these numbers
+// make up their ratio
+#[derive(Debug, Clone)]
+pub struct GearRule {
+    pub symbols: BTreeSet<char>,
+    pub neighbours: usize,
+    pub combine: Combine,
+}
+
+impl GearRule {
+    // Gives back the ratio of the gear at the position given the values of the numbers around it,
+    // or `None` if they are not as many as the rule asks for and the symbol is no gear
+    pub fn ratio(
+        &self,
+        position: Position,
+        values: &[u64],
+    ) -> miette::Result<Option<u64>, AocError> {
+        if values.len() != self.neighbours {
+            return Ok(None);
+        }
+        let ratio = self
+            .combine
+            .apply(values)
+            .ok_or_else(|| AocError::Overflow {
+                what: format!(
+                    "the ratio of the gear at line {}, column {}",
+                    position.0 + 1,
+                    position.1 + 1
+                ),
+            })?;
+        Ok(Some(ratio))
+    }
+}
 
-    for (xi, yi) in window {
-        if symbol_vec.contains(&(xi as usize, yi as usize)) {
-            return CharType {
-                value: character.value.clone(),
-                coordinates: character.coordinates,
-                related_gear: (xi as usize, yi as usize),
-            };
+impl Default for GearRule {
+    // The rule of the puzzle, a `*` next to exactly two numbers multiplied together
+    fn default() -> Self {
+        GearRule {
+            symbols: BTreeSet::from(['*']),
+            neighbours: 2,
+            combine: Combine::Product,
         }
     }
-    character.clone()
 }
 
-// gives back true if any number in the integer chain is true
-fn do_keep_integer(integer: &Vec<&CharType>) -> bool {
-    integer.iter().fold(false, |acc, character| {
-        acc || character.related_gear != (0, 0)
+fn add_ratio(res: u64, ratio: u64) -> miette::Result<u64, AocError> {
+    res.checked_add(ratio).ok_or_else(|| AocError::Overflow {
+        what: "the sum of the gear ratios".to_string(),
     })
 }
 
-// Computes the value of a chain of integer
-fn group_integer(group: &Vec<&CharType>) -> u32 {
-    group
-        .iter()
-        .rev()
-        .enumerate()
-        .fold(0, |acc, (index, character)| {
-            if let ValueType::Number(value) = character.value {
-                acc + (10 as u32).pow(index as u32) * value as u32
-            } else {
-                acc
-            }
-        })
+// Adds the ratios of the gears surrounded by as many numbers as the rule asks for to the total
+fn add_ratios(
+    mut res: u64,
+    gear_parts: BTreeMap<Position, Vec<u64>>,
+    rule: &GearRule,
+) -> miette::Result<u64, AocError> {
+    for (position, values) in gear_parts {
+        if let Some(ratio) = rule.ratio(position, &values)? {
+            res = add_ratio(res, ratio)?;
+        }
+    }
+
+    Ok(res)
 }
 
-fn max_group_gear(group: &Vec<&CharType>) -> (usize, usize) {
-    let res = group
-        .iter()
-        .max_by_key(|elt| elt.related_gear)
-        .expect("Should be a CharType")
-        .related_gear;
-    res
+pub fn process(input: &str) -> miette::Result<String, AocError> {
+    process_with(input, &Layout::default(), &GearRule::default())
 }
 
-// Flattens groups of integers (a.k.a a line) and gives back their sum
-fn flatten_groups(groups: &Vec<Vec<&CharType>>) -> Vec<GearPart> {
-    let mut integers = vec![];
+// Sums the ratios of the gears of the schematic, as defined by the rule, reading the schematic
+// with the given layout
+pub fn process_with(
+    input: &str,
+    layout: &Layout,
+    rule: &GearRule,
+) -> miette::Result<String, AocError> {
+    let grid = parse_schematic_with(input, &layout.classes)?;
+    let gears = SymbolIndex::with_neighbourhood(
+        &grid,
+        layout.neighbourhood,
+        |cell| matches!(cell, Cell::Symbol(symbol) if rule.symbols.contains(symbol)),
+    );
 
-    for group in groups.iter() {
-        if do_keep_integer(group) {
-            integers.push(GearPart {
-                value: group_integer(group),
-                gear: max_group_gear(group),
-            });
+    // Relates every number to every gear in its surrounding
+    let mut gear_parts = BTreeMap::<Position, Vec<u64>>::new();
+    for number in part_numbers(&grid)? {
+        for gear in gears.adjacent(&number) {
+            gear_parts.entry(gear).or_default().push(number.value);
         }
     }
-    integers
+
+    let res = add_ratios(0, gear_parts, rule)?;
+
+    Ok(res.to_string())
 }
 
-pub fn process(input: &str) -> miette::Result<String, AocError> {
-    // Process the grid as a 2d vector of characters
-    let grid = input
-        .trim()
-        .lines()
-        .map(|line| {
-            line.as_bytes()
-                .iter()
-                .map(|x| char::from_u32(*x as u32).expect("Should be a char"))
-                .collect::<Vec<char>>()
-        })
-        .collect::<Vec<Vec<char>>>();
-
-    // Make that grid into a 2d vector of an easier to process custom type
-    let processed_characters = grid
-        .iter()
-        .enumerate()
-        .map(|(x, line)| {
-            line.iter()
-                .enumerate()
-                .map(|(y, character)| filter_value(x, y, character))
-                .collect::<Vec<CharType>>()
-        })
-        .collect::<Vec<Vec<CharType>>>();
-
-    // Flattens the 2d vector because coordinates are now encoded into a tuple linked to the value
-    // of the character
-    let characters = processed_characters
-        .iter()
-        .flatten()
-        .collect::<Vec<&CharType>>();
-
-    // Gets only the characters that are numbers
-    let numbers = characters
-        .iter()
-        .filter(|x| matches!(x.value, ValueType::Number(_)))
-        .collect::<Vec<_>>();
-
-    // Gets only the characters that are Symbols
-    let symbols = characters
-        .clone()
-        .iter()
-        .filter(|x| matches!(x.value, ValueType::Gear((_, _))))
-        .map(|x| x.coordinates)
-        .collect::<Vec<(usize, usize)>>();
-
-    // Processes the numbers to find out which one are next to symbols
-    let associated_numbers = numbers
-        .into_iter()
-        .map(|number| filter_keepable(number, &symbols))
-        .collect::<Vec<CharType>>();
+// Same as `process_with`, but checks bands of `band_rows` rows in parallel. Every gear belongs to
+// the band of its row, and the numbers around it are looked up across bands. The sums of the
+// bands are added in order, so that the result does not depend on how the bands are scheduled.
+pub fn process_par(
+    input: &str,
+    layout: &Layout,
+    rule: &GearRule,
+    band_rows: usize,
+) -> miette::Result<String, AocError> {
+    let grid = parse_schematic_with(input, &layout.classes)?;
+    let gears = SymbolIndex::with_neighbourhood(
+        &grid,
+        layout.neighbourhood,
+        |cell| matches!(cell, Cell::Symbol(symbol) if rule.symbols.contains(symbol)),
+    );
+    let band_rows = band_rows.max(1);
 
-    let mut final_process = vec![];
+    let bands = (0..grid.height().div_ceil(band_rows))
+        .into_par_iter()
+        .map(|band| {
+            let rows = band * band_rows..((band + 1) * band_rows).min(grid.height());
 
-    // Groups lines together
-    for (_, groups) in &associated_numbers
-        .into_iter()
-        .group_by(|elt| elt.coordinates.0)
-    {
-        let groups = groups.collect::<Vec<CharType>>();
+            // The gears of the band may be surrounded by numbers of the rows right outside of it
+            let mut gear_parts = BTreeMap::<Position, Vec<u64>>::new();
+            for row in rows.start.saturating_sub(1)..(rows.end + 1).min(grid.height()) {
+                for number in row_numbers(row, grid.row(row).unwrap_or(&[])) {
+                    let number = number?;
+                    for gear in gears.adjacent(&number) {
+                        if rows.contains(&gear.0) {
+                            gear_parts.entry(gear).or_default().push(number.value);
+                        }
+                    }
+                }
+            }
+
+            add_ratios(0, gear_parts, rule)
+        })
+        .collect::<Vec<miette::Result<u64, AocError>>>();
 
-        // Groups contiguous strings of numbers together
-        let groups = (&(0..groups.len()).group_by(|&i| groups[i].coordinates.1 as usize - i))
-            .into_iter()
-            .map(|(_, group)| group.map(|i| &groups[i]).collect::<Vec<&CharType>>())
-            .collect::<Vec<Vec<&CharType>>>();
+    let res = bands
+        .into_iter()
+        .try_fold(0, |res, band| add_ratio(res, band?))?;
 
-        // Processes the value of each string of number in the line and sums them up
-        final_process.push(flatten_groups(&groups));
-    }
+    Ok(res.to_string())
+}
 
+// Same as `process_with`, but reads the schematic row by row so that only the rows around the
+// gears being checked are kept in memory
+pub fn process_reader(
+    reader: impl BufRead,
+    layout: &Layout,
+    rule: &GearRule,
+) -> miette::Result<String, AocError> {
     let mut res = 0;
 
-    for (_, vectors) in &final_process
-        .iter()
-        .flatten()
-        .sorted_by(|a, b| Ord::cmp(&a.gear, &b.gear))
-        .group_by(|elt| elt.gear)
-    {
-        let vectors = vectors.collect::<Vec<_>>();
-        if vectors.len() >= 2 {
-            res = res + (vectors[0].value * vectors[1].value);
+    for_each_window(reader, &layout.classes, |window| {
+        let gears = SymbolIndex::with_neighbourhood(
+            &window.cells,
+            layout.neighbourhood,
+            |cell| matches!(cell, Cell::Symbol(symbol) if rule.symbols.contains(symbol)),
+        );
+
+        // Relates the numbers of the window to the gears of its centre row only, so that every
+        // gear is seen once
+        let mut gear_parts = BTreeMap::<Position, Vec<u64>>::new();
+        for index in 0..window.cells.height() {
+            for number in window.numbers(index) {
+                let number = number?;
+                for (row, column) in gears.adjacent(&number) {
+                    if row == window.centre {
+                        gear_parts
+                            .entry((window.first_row + row, column))
+                            .or_default()
+                            .push(number.value);
+                    }
+                }
+            }
         }
-    }
+
+        res = add_ratios(res, gear_parts, rule)?;
+        Ok(())
+    })?;
 
     Ok(res.to_string())
 }
@@ -207,6 +250,48 @@ pub fn process(input: &str) -> miette::Result<String, AocError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schematic::Neighbourhood;
+
+    const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    // Schematics on which every way of processing them must agree, edge cases included
+    const INPUTS: [&str; 13] = [
+        EXAMPLE,
+        "*2.3*\n4...5\n..*..\n6...7\n*8.9*",
+        "3.....\n.*12*.\n.....4",
+        "3.....\r\n.*12*.\r\n.....4\r\n",
+        "2*3",
+        "",
+        "2..\n*  \n3..",
+        "  2\n*3.",
+        "\n\n2*3\n\n",
+        "2*3\n\n...",
+        "2*3\n...\n  ",
+        " \t\n2*3\n \r\n\n",
+        "2*3\n..",
+    ];
+
+    // Gear rules the schematics are processed with, one of them counting lone numbers next to
+    // any of several symbols
+    fn rules() -> [GearRule; 2] {
+        [
+            GearRule::default(),
+            GearRule {
+                symbols: BTreeSet::from(['*', '#', '$']),
+                neighbours: 1,
+                combine: Combine::Sum,
+            },
+        ]
+    }
 
     #[test]
     fn test_process() -> miette::Result<()> {
@@ -223,4 +308,134 @@ mod tests {
         assert_eq!("467835", process(input)?);
         Ok(())
     }
+
+    #[test]
+    fn test_gear_on_edges() -> miette::Result<()> {
+        // Gears in every corner and along every edge, including the origin
+        let input = "*2.3*
+4...5
+..*..
+6...7
+*8.9*";
+        assert_eq!("134", process(input)?);
+        let input = "2.3
+.*.
+...";
+        assert_eq!("6", process(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_shared_numbers() -> miette::Result<()> {
+        // 12 sits between both gears and counts toward each of them
+        let input = "3.....
+.*12*.
+.....4";
+        assert_eq!("84", process(input)?);
+        // A number touching the same gear through several digits counts once
+        let input = "123.
+.*..
+5...";
+        assert_eq!("615", process(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_gear_rule() -> miette::Result<()> {
+        let input = "2.3.4
+.*...
+5.#.6
+...#.
+7.8..";
+        // Only the `*` next to exactly two numbers is a gear under the puzzle rule
+        assert_eq!("0", process(input)?);
+
+        let rule = GearRule {
+            neighbours: 3,
+            ..GearRule::default()
+        };
+        assert_eq!("30", process_with(input, &Layout::default(), &rule)?);
+
+        let rule = GearRule {
+            symbols: BTreeSet::from(['#']),
+            neighbours: 2,
+            combine: Combine::Sum,
+        };
+        assert_eq!("14", process_with(input, &Layout::default(), &rule)?);
+
+        let rule = GearRule {
+            symbols: BTreeSet::from(['*', '#']),
+            neighbours: 3,
+            combine: Combine::Custom(Arc::new(|values| values.iter().max().copied())),
+        };
+        assert_eq!("5", process_with(input, &Layout::default(), &rule)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_layout() -> miette::Result<()> {
+        let input = "12.
+.*3
+4..";
+        assert_eq!("0", process(input)?);
+        let layout = Layout {
+            neighbourhood: Neighbourhood::Four,
+            ..Layout::default()
+        };
+        assert_eq!("36", process_with(input, &layout, &GearRule::default())?);
+        let rule = GearRule {
+            neighbours: 3,
+            ..GearRule::default()
+        };
+        assert_eq!("144", process_with(input, &Layout::default(), &rule)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wide_ratios() -> miette::Result<()> {
+        // Ratios past 32 bits are kept, those past 64 bits are reported
+        let input = "99999*88888\n...........";
+        assert_eq!("8888711112", process(input)?);
+        let input = "9999999999*8888888888\n.....................";
+        assert!(matches!(
+            process(input),
+            Err(AocError::Overflow { what }) if what == "the ratio of the gear at line 1, column 11"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_reader() -> miette::Result<()> {
+        for input in INPUTS {
+            for rule in rules().iter() {
+                assert_eq!(
+                    process_with(input, &Layout::default(), rule)
+                        .map_err(|error| error.to_string()),
+                    process_reader(input.as_bytes(), &Layout::default(), rule)
+                        .map_err(|error| error.to_string())
+                );
+            }
+        }
+        assert_eq!("6", process("2*3\n...\n  ")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_par() -> miette::Result<()> {
+        // Bands of every size, so that gears and numbers fall on both sides of their boundaries
+        for input in INPUTS {
+            for rule in rules().iter() {
+                let sequential = process_with(input, &Layout::default(), rule)
+                    .map_err(|error| error.to_string());
+                for band_rows in 0..=11 {
+                    assert_eq!(
+                        sequential,
+                        process_par(input, &Layout::default(), rule, band_rows)
+                            .map_err(|error| error.to_string())
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
 }
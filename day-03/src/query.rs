@@ -0,0 +1,317 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use itertools::Itertools;
+
+use crate::{
+    custom_error::AocError,
+    grid::{Grid, Position},
+    part2::GearRule,
+    schematic::{
+        parse_schematic_with, part_numbers, Cell, Layout, Neighbourhood, PartNumber, SymbolIndex,
+    },
+};
+
+// A gear of the schematic along with the numbers surrounding it and their ratio
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gear {
+    pub position: Position,
+    pub symbol: char,
+    pub numbers: Vec<PartNumber>,
+    pub ratio: u64,
+}
+
+// A parsed schematic that can be asked about its cells, numbers and gears
+#[derive(Debug, Clone)]
+pub struct Schematic {
+    cells: Grid<Cell>,
+    numbers: Vec<PartNumber>,
+    // The index in `numbers` of the number every cell is a digit of
+    owners: Grid<Option<usize>>,
+    symbols: SymbolIndex,
+    neighbourhood: Neighbourhood,
+}
+
+impl Schematic {
+    // Parses a schematic, reading it with the given layout
+    pub fn parse_with(input: &str, layout: &Layout) -> miette::Result<Schematic, AocError> {
+        let cells = parse_schematic_with(input, &layout.classes)?;
+        let numbers = part_numbers(&cells)?;
+
+        let mut owners = cells.map(|_| None);
+        for (index, number) in numbers.iter().enumerate() {
+            for column in number.col_start..number.col_end {
+                if let Some(owner) = owners.get_mut((number.row, column)) {
+                    *owner = Some(index);
+                }
+            }
+        }
+
+        let symbols = SymbolIndex::with_neighbourhood(&cells, layout.neighbourhood, |cell| {
+            matches!(cell, Cell::Symbol(_))
+        });
+
+        Ok(Schematic {
+            cells,
+            numbers,
+            owners,
+            symbols,
+            neighbourhood: layout.neighbourhood,
+        })
+    }
+
+    pub fn cells(&self) -> &Grid<Cell> {
+        &self.cells
+    }
+
+    // Every number of the schematic, row after row, surrounded by a symbol or not
+    pub fn numbers(&self) -> &[PartNumber] {
+        &self.numbers
+    }
+
+    // The numbers surrounded by a symbol, the ones part 1 sums
+    pub fn part_numbers(&self) -> impl Iterator<Item = &PartNumber> {
+        self.numbers
+            .iter()
+            .filter(|number| self.symbols.touches(number))
+    }
+
+    // The number the cell is a digit of
+    pub fn number_at(&self, position: Position) -> Option<&PartNumber> {
+        let index = (*self.owners.get(position)?)?;
+        Some(&self.numbers[index])
+    }
+
+    // Tells if the cell is a digit of a number surrounded by a symbol
+    pub fn is_part_number_cell(&self, position: Position) -> bool {
+        self.number_at(position)
+            .is_some_and(|number| self.symbols.touches(number))
+    }
+
+    // The numbers surrounding the cell, left aside the one it is a digit of, each listed once
+    pub fn numbers_around(&self, position: Position) -> Vec<&PartNumber> {
+        let own = self.owners.get(position).copied().flatten();
+        let neighbours = match self.neighbourhood {
+            Neighbourhood::Four => self.cells.neighbours4(position).collect::<Vec<Position>>(),
+            Neighbourhood::Eight => self.cells.neighbours8(position).collect::<Vec<Position>>(),
+        };
+
+        neighbours
+            .into_iter()
+            .filter_map(|neighbour| self.owners.get(neighbour).copied().flatten())
+            .filter(|index| Some(*index) != own)
+            .unique()
+            .map(|index| &self.numbers[index])
+            .collect()
+    }
+
+    // The symbols surrounding the number, along with their positions
+    pub fn symbols_around(&self, number: &PartNumber) -> Vec<(Position, char)> {
+        number
+            .border_with(&self.cells, self.neighbourhood)
+            .filter_map(|position| match self.cells.get(position) {
+                Some(Cell::Symbol(symbol)) => Some((position, *symbol)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Every gear of the schematic under the rule along with its ratio, row after row. Numbers
+    // are related to gears and the rule applied the same way as in part 2.
+    pub fn gears(&self, rule: &GearRule) -> miette::Result<Vec<Gear>, AocError> {
+        let index = SymbolIndex::with_neighbourhood(
+            &self.cells,
+            self.neighbourhood,
+            |cell| matches!(cell, Cell::Symbol(symbol) if rule.symbols.contains(symbol)),
+        );
+        let mut gear_parts = BTreeMap::<Position, Vec<PartNumber>>::new();
+        for number in self.numbers.iter() {
+            for gear in index.adjacent(number) {
+                gear_parts.entry(gear).or_default().push(*number);
+            }
+        }
+
+        let mut gears = vec![];
+        for (position, numbers) in gear_parts {
+            let values = numbers
+                .iter()
+                .map(|number| number.value)
+                .collect::<Vec<u64>>();
+            let Some(ratio) = rule.ratio(position, &values)? else {
+                continue;
+            };
+            let Some(Cell::Symbol(symbol)) = self.cells.get(position) else {
+                continue;
+            };
+            gears.push(Gear {
+                position,
+                symbol: *symbol,
+                numbers,
+                ratio,
+            });
+        }
+
+        Ok(gears)
+    }
+}
+
+impl FromStr for Schematic {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Schematic::parse_with(s, &Layout::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::{part1, part2, part2::Combine};
+
+    const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    fn values(numbers: &[&PartNumber]) -> Vec<u64> {
+        numbers.iter().map(|number| number.value).collect()
+    }
+
+    #[test]
+    fn test_numbers_around() -> miette::Result<()> {
+        let schematic = EXAMPLE.parse::<Schematic>()?;
+
+        assert_eq!(vec![467, 35], values(&schematic.numbers_around((1, 3))));
+        assert_eq!(vec![755, 598], values(&schematic.numbers_around((8, 5))));
+        assert!(schematic.numbers_around((7, 0)).is_empty());
+        assert_eq!(vec![467, 35], values(&schematic.numbers_around((1, 2))));
+        // A digit is not surrounded by its own number
+        assert!(schematic.numbers_around((0, 2)).is_empty());
+
+        let layout = Layout {
+            neighbourhood: Neighbourhood::Four,
+            ..Layout::default()
+        };
+        let schematic = Schematic::parse_with(EXAMPLE, &layout)?;
+        assert_eq!(vec![35], values(&schematic.numbers_around((1, 3))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_symbols_around() -> miette::Result<()> {
+        let schematic = EXAMPLE.parse::<Schematic>()?;
+        let number = *schematic.number_at((2, 7)).unwrap();
+
+        assert_eq!(633, number.value);
+        assert_eq!(vec![((3, 6), '#')], schematic.symbols_around(&number));
+        assert!(schematic.symbols_around(&schematic.numbers()[1]).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_number_cells() -> miette::Result<()> {
+        let schematic = EXAMPLE.parse::<Schematic>()?;
+
+        assert!(schematic.is_part_number_cell((0, 0)));
+        assert!(schematic.is_part_number_cell((9, 7)));
+        // 114 and 58 are next to no symbol, and symbols or empty cells are no digits
+        assert!(!schematic.is_part_number_cell((0, 5)));
+        assert!(!schematic.is_part_number_cell((5, 8)));
+        assert!(!schematic.is_part_number_cell((1, 3)));
+        assert!(!schematic.is_part_number_cell((0, 3)));
+        assert!(!schematic.is_part_number_cell((10, 0)));
+        assert_eq!(None, schematic.number_at((0, 3)));
+
+        assert_eq!(
+            part1::process(EXAMPLE)?,
+            schematic
+                .part_numbers()
+                .map(|number| number.value)
+                .sum::<u64>()
+                .to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_gears() -> miette::Result<()> {
+        let schematic = EXAMPLE.parse::<Schematic>()?;
+        let gears = schematic.gears(&GearRule::default())?;
+
+        assert_eq!(
+            vec![((1, 3), 16345), ((8, 5), 451490)],
+            gears
+                .iter()
+                .map(|gear| (gear.position, gear.ratio))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            part2::process(EXAMPLE)?,
+            gears.iter().map(|gear| gear.ratio).sum::<u64>().to_string()
+        );
+
+        let rule = GearRule {
+            neighbours: 1,
+            ..GearRule::default()
+        };
+        assert_eq!(
+            vec![(4, 3)],
+            schematic
+                .gears(&rule)?
+                .iter()
+                .map(|gear| gear.position)
+                .collect::<Vec<Position>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_gears_match_part2() -> miette::Result<()> {
+        let rules = [
+            GearRule::default(),
+            GearRule {
+                symbols: BTreeSet::from(['*', '#', '$']),
+                neighbours: 1,
+                combine: Combine::Sum,
+            },
+            GearRule {
+                neighbours: 0,
+                ..GearRule::default()
+            },
+        ];
+        let inputs = [EXAMPLE, "2*3\n.4.", "*2.3*\n4...5\n..*..\n6...7\n*8.9*"];
+
+        for input in inputs {
+            for rule in rules.iter() {
+                let ratios = input
+                    .parse::<Schematic>()?
+                    .gears(rule)?
+                    .iter()
+                    .map(|gear| gear.ratio)
+                    .sum::<u64>();
+                assert_eq!(
+                    part2::process_with(input, &Layout::default(), rule)?,
+                    ratios.to_string()
+                );
+            }
+        }
+
+        // Both report the same gear when its ratio overflows
+        let input = "9999999999*8888888888\n.....................";
+        let schematic = input.parse::<Schematic>()?;
+        let (Err(part2), Err(query)) =
+            (part2::process(input), schematic.gears(&GearRule::default()))
+        else {
+            panic!("the ratio of the gear should overflow");
+        };
+        assert_eq!(part2.to_string(), query.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+    custom_error::AocError,
+    grid::{Grid, Position},
+    schematic::{part_numbers, Cell, Neighbourhood, PartNumber, SymbolIndex},
+};
+
+// A symbol of the schematic along with the numbers surrounding it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolEntry {
+    pub position: Position,
+    pub symbol: char,
+    pub numbers: Vec<PartNumber>,
+    pub sum: u64,
+}
+
+// The symbols of a same character, a number surrounded by several of them counting once for each
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolTotals {
+    pub symbol: char,
+    pub symbols: usize,
+    pub numbers: usize,
+    pub sum: u64,
+}
+
+// The numbers surrounding every symbol of a schematic, and those surrounding none
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolReport {
+    pub entries: Vec<SymbolEntry>,
+    pub totals: Vec<SymbolTotals>,
+    pub lone: Vec<PartNumber>,
+}
+
+// Relates every symbol to the numbers around it, symbols being listed row after row and their
+// totals by character
+pub fn symbol_report(
+    grid: &Grid<Cell>,
+    neighbourhood: Neighbourhood,
+) -> miette::Result<SymbolReport, AocError> {
+    let symbols = SymbolIndex::with_neighbourhood(grid, neighbourhood, |cell| {
+        matches!(cell, Cell::Symbol(_))
+    });
+
+    let mut adjacent = BTreeMap::<Position, Vec<PartNumber>>::new();
+    let mut lone = vec![];
+    for number in part_numbers(grid)? {
+        let mut surrounded = false;
+        for position in symbols.adjacent(&number) {
+            adjacent.entry(position).or_default().push(number);
+            surrounded = true;
+        }
+        if !surrounded {
+            lone.push(number);
+        }
+    }
+
+    let mut entries = vec![];
+    for (position, cell) in grid.iter() {
+        let Cell::Symbol(symbol) = cell else {
+            continue;
+        };
+        let numbers = adjacent.remove(&position).unwrap_or_default();
+        let sum = numbers
+            .iter()
+            .try_fold(0_u64, |sum, number| sum.checked_add(number.value))
+            .ok_or_else(|| AocError::Overflow {
+                what: format!(
+                    "the sum around the symbol at line {}, column {}",
+                    position.0 + 1,
+                    position.1 + 1
+                ),
+            })?;
+        entries.push(SymbolEntry {
+            position,
+            symbol: *symbol,
+            numbers,
+            sum,
+        });
+    }
+
+    let mut totals = BTreeMap::<char, SymbolTotals>::new();
+    for entry in entries.iter() {
+        let totals = totals.entry(entry.symbol).or_insert(SymbolTotals {
+            symbol: entry.symbol,
+            symbols: 0,
+            numbers: 0,
+            sum: 0,
+        });
+        totals.symbols += 1;
+        totals.numbers += entry.numbers.len();
+        totals.sum = totals
+            .sum
+            .checked_add(entry.sum)
+            .ok_or_else(|| AocError::Overflow {
+                what: format!("the sum around the `{}` symbols", entry.symbol),
+            })?;
+    }
+
+    Ok(SymbolReport {
+        entries,
+        totals: totals.into_values().collect(),
+        lone,
+    })
+}
+
+// Prints each symbol with its numbers, then the totals per character and the lone numbers, e.g.
+//     line 2, column 4 `*`: 467 + 35 = 502
+//     `*`: 3 symbol(s), 5 number(s), sum 2472
+//     114 at line 1, column 6 is next to no symbol
+impl fmt::Display for SymbolReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in self.entries.iter() {
+            let (row, column) = entry.position;
+            let numbers = entry
+                .numbers
+                .iter()
+                .map(|number| number.value.to_string())
+                .collect::<Vec<String>>();
+            writeln!(
+                f,
+                "line {}, column {} `{}`: {} = {}",
+                row + 1,
+                column + 1,
+                entry.symbol,
+                if numbers.is_empty() {
+                    "no number".to_string()
+                } else {
+                    numbers.join(" + ")
+                },
+                entry.sum
+            )?;
+        }
+        for totals in self.totals.iter() {
+            writeln!(
+                f,
+                "`{}`: {} symbol(s), {} number(s), sum {}",
+                totals.symbol, totals.symbols, totals.numbers, totals.sum
+            )?;
+        }
+        for number in self.lone.iter() {
+            writeln!(
+                f,
+                "{} at line {}, column {} is next to no symbol",
+                number.value,
+                number.row + 1,
+                number.col_start + 1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schematic::parse_schematic;
+
+    #[test]
+    fn test_symbol_report() -> miette::Result<()> {
+        let input = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+        let report = symbol_report(&parse_schematic(input)?, Neighbourhood::Eight)?;
+
+        assert_eq!(
+            vec![
+                ((1, 3), '*', 502),
+                ((3, 6), '#', 633),
+                ((4, 3), '*', 617),
+                ((5, 5), '+', 592),
+                ((8, 3), '$', 664),
+                ((8, 5), '*', 1353)
+            ],
+            report
+                .entries
+                .iter()
+                .map(|entry| (entry.position, entry.symbol, entry.sum))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                ('#', 1, 1, 633),
+                ('$', 1, 1, 664),
+                ('*', 3, 5, 2472),
+                ('+', 1, 1, 592)
+            ],
+            report
+                .totals
+                .iter()
+                .map(|totals| (totals.symbol, totals.symbols, totals.numbers, totals.sum))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![114, 58],
+            report
+                .lone
+                .iter()
+                .map(|number| number.value)
+                .collect::<Vec<u64>>()
+        );
+        assert!(report
+            .to_string()
+            .starts_with("line 2, column 4 `*`: 467 + 35 = 502\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_shared_numbers() -> miette::Result<()> {
+        // A number between two symbols counts toward both, and a lone symbol sums to nothing
+        let report = symbol_report(&parse_schematic("#12*\n....\n...$")?, Neighbourhood::Eight)?;
+
+        assert_eq!(
+            vec![12, 12, 0],
+            report
+                .entries
+                .iter()
+                .map(|entry| entry.sum)
+                .collect::<Vec<u64>>()
+        );
+        assert_eq!(3, report.totals.len());
+        assert!(report.lone.is_empty());
+        assert!(report
+            .to_string()
+            .contains("line 3, column 4 `$`: no number = 0\n"));
+        Ok(())
+    }
+}
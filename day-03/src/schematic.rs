@@ -0,0 +1,410 @@
+use std::{collections::BTreeSet, str::FromStr};
+
+use crate::{
+    custom_error::AocError,
+    grid::{Grid, Position},
+};
+
+// A cell of the engine schematic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    Digit(u8),
+    Empty,
+    Symbol(char),
+}
+
+impl From<char> for Cell {
+    fn from(character: char) -> Self {
+        CharClasses::default().classify(character)
+    }
+}
+
+// Which characters are symbols, every one neither a digit nor empty by default
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SymbolClass {
+    #[default]
+    AnyOther,
+    Only(BTreeSet<char>),
+}
+
+// How the characters of the schematic are read, digits always being digits, and characters
+// outside of both classes being read as empty
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharClasses {
+    pub empty: BTreeSet<char>,
+    pub symbols: SymbolClass,
+}
+
+impl Default for CharClasses {
+    fn default() -> Self {
+        CharClasses {
+            empty: BTreeSet::from(['.']),
+            symbols: SymbolClass::AnyOther,
+        }
+    }
+}
+
+impl CharClasses {
+    // Reads a line of the schematic into its cells, leaving out its line ending
+    pub fn classify_line(&self, line: &str) -> Vec<Cell> {
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        line.chars()
+            .map(|character| self.classify(character))
+            .collect()
+    }
+
+    pub fn classify(&self, character: char) -> Cell {
+        match character.to_digit(10) {
+            Some(digit) => Cell::Digit(digit as u8),
+            None if self.empty.contains(&character) => Cell::Empty,
+            None => match &self.symbols {
+                SymbolClass::AnyOther => Cell::Symbol(character),
+                SymbolClass::Only(symbols) if symbols.contains(&character) => {
+                    Cell::Symbol(character)
+                }
+                SymbolClass::Only(_) => Cell::Empty,
+            },
+        }
+    }
+}
+
+// Which cells surround another, those sharing a side or also those sharing a corner
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Neighbourhood {
+    Four,
+    #[default]
+    Eight,
+}
+
+impl FromStr for Neighbourhood {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "4" => Ok(Neighbourhood::Four),
+            "8" => Ok(Neighbourhood::Eight),
+            _ => Err(AocError::UnknownNeighbourhood(s.to_string())),
+        }
+    }
+}
+
+// How a schematic is read, defaulting to the puzzle's conventions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Layout {
+    pub classes: CharClasses,
+    pub neighbourhood: Neighbourhood,
+}
+
+// Parses the engine schematic into a grid of cells
+pub fn parse_schematic(input: &str) -> miette::Result<Grid<Cell>, AocError> {
+    parse_schematic_with(input, &CharClasses::default())
+}
+
+// Parses the engine schematic into a grid of cells, reading characters with the given classes.
+// Lines may end with `\r\n`, and the blank lines, empty or of whitespace only, before and after
+// the schematic are left out. Any other line is a row, even one of spaces only.
+pub fn parse_schematic_with(
+    input: &str,
+    classes: &CharClasses,
+) -> miette::Result<Grid<Cell>, AocError> {
+    let lines = input.split('\n').collect::<Vec<&str>>();
+    let start = lines
+        .iter()
+        .position(|line| !line.trim().is_empty())
+        .unwrap_or(lines.len());
+    let end = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(start, |end| end + 1);
+
+    Grid::from_rows(
+        lines[start..end]
+            .iter()
+            .map(|line| classes.classify_line(line))
+            .collect(),
+    )
+}
+
+// A number of the schematic, spanning the columns `col_start..col_end` of its row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartNumber {
+    pub value: u64,
+    pub row: usize,
+    pub col_start: usize,
+    pub col_end: usize,
+}
+
+impl PartNumber {
+    // Iterates over the cells surrounding the number, leaving out those outside of the grid
+    pub fn border<'a, T>(&self, grid: &'a Grid<T>) -> impl Iterator<Item = Position> + 'a {
+        let number = *self;
+        let rows = number.row.saturating_sub(1)..(number.row + 2).min(grid.height());
+        let columns = number.col_start.saturating_sub(1)..(number.col_end + 1).min(grid.width());
+
+        rows.flat_map(move |row| columns.clone().map(move |column| (row, column)))
+            .filter(move |position| !number.contains(*position))
+    }
+
+    // Iterates over the cells surrounding the number in the given neighbourhood, leaving out those
+    // outside of the grid
+    pub fn border_with<'a, T>(
+        &self,
+        grid: &'a Grid<T>,
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = Position> + 'a {
+        let number = *self;
+
+        // Sharing a side with a digit means being on its row or its column
+        self.border(grid).filter(move |(row, column)| {
+            neighbourhood == Neighbourhood::Eight
+                || *row == number.row
+                || (number.col_start..number.col_end).contains(column)
+        })
+    }
+
+    // Tells if the cell is one of the number's digits
+    pub fn contains(&self, (row, column): Position) -> bool {
+        row == self.row && (self.col_start..self.col_end).contains(&column)
+    }
+}
+
+// Iterates over the numbers of a row of the schematic, left to right, failing on those too
+// wide for 64 bits
+pub fn row_numbers(
+    row: usize,
+    cells: &[Cell],
+) -> impl Iterator<Item = miette::Result<PartNumber, AocError>> + '_ {
+    let mut column = 0;
+
+    std::iter::from_fn(move || {
+        let col_start = column
+            + cells[column..]
+                .iter()
+                .position(|cell| matches!(cell, Cell::Digit(_)))?;
+        let mut value = Some(0_u64);
+        column = col_start;
+        while let Some(Cell::Digit(digit)) = cells.get(column) {
+            value = value
+                .and_then(|value| value.checked_mul(10))
+                .and_then(|value| value.checked_add(*digit as u64));
+            column += 1;
+        }
+
+        Some(match value {
+            Some(value) => Ok(PartNumber {
+                value,
+                row,
+                col_start,
+                col_end: column,
+            }),
+            None => Err(AocError::Overflow {
+                what: format!("the number at line {}, column {}", row + 1, col_start + 1),
+            }),
+        })
+    })
+}
+
+// Gives back every number of the schematic, row after row
+pub fn part_numbers(grid: &Grid<Cell>) -> miette::Result<Vec<PartNumber>, AocError> {
+    grid.rows()
+        .enumerate()
+        .flat_map(|(row, cells)| row_numbers(row, cells))
+        .collect()
+}
+
+// The positions of the symbols of a schematic, indexed by cell for constant time lookups
+#[derive(Debug, Clone)]
+pub struct SymbolIndex {
+    cells: Grid<bool>,
+    neighbourhood: Neighbourhood,
+}
+
+impl SymbolIndex {
+    // Indexes the cells of the schematic the predicate holds for
+    pub fn new(grid: &Grid<Cell>, is_symbol: impl Fn(&Cell) -> bool) -> SymbolIndex {
+        SymbolIndex::with_neighbourhood(grid, Neighbourhood::default(), is_symbol)
+    }
+
+    // Indexes the cells of the schematic the predicate holds for, numbers being surrounded by
+    // the given neighbourhood
+    pub fn with_neighbourhood(
+        grid: &Grid<Cell>,
+        neighbourhood: Neighbourhood,
+        is_symbol: impl Fn(&Cell) -> bool,
+    ) -> SymbolIndex {
+        SymbolIndex {
+            cells: grid.map(is_symbol),
+            neighbourhood,
+        }
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.cells.get(position).copied().unwrap_or(false)
+    }
+
+    // Iterates over the indexed symbols surrounding the number
+    pub fn adjacent<'a>(&'a self, number: &PartNumber) -> impl Iterator<Item = Position> + 'a {
+        number
+            .border_with(&self.cells, self.neighbourhood)
+            .filter(|position| self.contains(*position))
+    }
+
+    // Tells if any indexed symbol surrounds the number
+    pub fn touches(&self, number: &PartNumber) -> bool {
+        self.adjacent(number).next().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn test_row_numbers() -> miette::Result<()> {
+        let cells = "467..114.*12"
+            .chars()
+            .map(Cell::from)
+            .collect::<Vec<Cell>>();
+
+        assert_eq!(
+            vec![(467, 0, 3), (114, 5, 8), (12, 10, 12)],
+            row_numbers(3, &cells)
+                .map(|number| number.map(|number| (number.value, number.col_start, number.col_end)))
+                .collect::<miette::Result<Vec<_>, AocError>>()?
+        );
+        assert!(row_numbers(3, &cells).all(|number| number.is_ok_and(|number| number.row == 3)));
+        assert_eq!(0, row_numbers(0, &[Cell::Empty, Cell::Symbol('#')]).count());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wide_numbers() -> miette::Result<()> {
+        let grid = parse_schematic("18446744073709551615.\n.....................")?;
+        assert_eq!(u64::MAX, part_numbers(&grid)?[0].value);
+
+        let grid = parse_schematic("..18446744073709551616\n......................")?;
+        assert!(matches!(
+            part_numbers(&grid),
+            Err(AocError::Overflow { what }) if what == "the number at line 1, column 3"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_border() -> miette::Result<()> {
+        let grid = parse_schematic("....\n.12.\n....")?;
+        let number = part_numbers(&grid)?[0];
+
+        assert_eq!(
+            vec![
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (0, 3),
+                (1, 0),
+                (1, 3),
+                (2, 0),
+                (2, 1),
+                (2, 2),
+                (2, 3)
+            ],
+            number.border(&grid).collect::<Vec<Position>>()
+        );
+
+        // Numbers against the edges of the grid keep only the cells inside of it
+        let grid = parse_schematic("12.\n..3")?;
+        let numbers = part_numbers(&grid)?;
+        assert_eq!(
+            vec![(0, 2), (1, 0), (1, 1), (1, 2)],
+            numbers[0].border(&grid).collect::<Vec<Position>>()
+        );
+        assert_eq!(
+            vec![(0, 1), (0, 2), (1, 1)],
+            numbers[1].border(&grid).collect::<Vec<Position>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_symbol_index() -> miette::Result<()> {
+        let grid = parse_schematic("467..114..\n...*......\n..35..633.\n......#...")?;
+        let symbols = SymbolIndex::new(&grid, |cell| matches!(cell, Cell::Symbol(_)));
+        let gears = SymbolIndex::new(&grid, |cell| *cell == Cell::Symbol('*'));
+
+        assert!(symbols.contains((1, 3)) && symbols.contains((3, 6)));
+        assert!(!gears.contains((3, 6)));
+        assert!(!symbols.contains((4, 0)));
+        assert_eq!(
+            vec![467, 35, 633],
+            part_numbers(&grid)?
+                .iter()
+                .filter(|number| symbols.touches(number))
+                .map(|number| number.value)
+                .collect::<Vec<u64>>()
+        );
+        assert_eq!(
+            vec![(1, 3)],
+            part_numbers(&grid)?
+                .iter()
+                .flat_map(|number| gears.adjacent(number).collect::<Vec<Position>>())
+                .unique()
+                .collect::<Vec<Position>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_char_classes() -> miette::Result<()> {
+        let classes = CharClasses {
+            empty: BTreeSet::from(['.', ' ']),
+            symbols: SymbolClass::Only(BTreeSet::from(['#', '*'])),
+        };
+
+        assert_eq!(Cell::Digit(7), classes.classify('7'));
+        assert_eq!(Cell::Empty, classes.classify(' '));
+        assert_eq!(Cell::Symbol('#'), classes.classify('#'));
+        assert_eq!(Cell::Empty, classes.classify('$'));
+        assert_eq!(Cell::Symbol('$'), Cell::from('$'));
+
+        // Line endings are never read as symbols
+        let grid = parse_schematic("1.\r\n.#\r\n")?;
+        assert_eq!((2, 2), (grid.width(), grid.height()));
+        assert_eq!(Some(&Cell::Empty), grid.get((0, 1)));
+        assert_eq!(Some(&Cell::Symbol('#')), grid.get((1, 1)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_neighbourhood() -> miette::Result<()> {
+        let grid = parse_schematic(".....\n.12..\n.....")?;
+        let number = part_numbers(&grid)?[0];
+
+        assert_eq!(
+            vec![(0, 1), (0, 2), (1, 0), (1, 3), (2, 1), (2, 2)],
+            number
+                .border_with(&grid, Neighbourhood::Four)
+                .collect::<Vec<Position>>()
+        );
+        assert_eq!(
+            number.border(&grid).collect::<Vec<Position>>(),
+            number
+                .border_with(&grid, Neighbourhood::Eight)
+                .collect::<Vec<Position>>()
+        );
+
+        let grid = parse_schematic("#..\n.1.\n..#")?;
+        let number = part_numbers(&grid)?[0];
+        let symbols = |neighbourhood| {
+            SymbolIndex::with_neighbourhood(&grid, neighbourhood, |cell| {
+                matches!(cell, Cell::Symbol(_))
+            })
+        };
+        assert!(symbols(Neighbourhood::Eight).touches(&number));
+        assert!(!symbols(Neighbourhood::Four).touches(&number));
+        assert!("6".parse::<Neighbourhood>().is_err());
+        Ok(())
+    }
+}
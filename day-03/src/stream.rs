@@ -0,0 +1,179 @@
+use std::{collections::VecDeque, io::BufRead};
+
+use crate::{
+    custom_error::AocError,
+    grid::Grid,
+    schematic::{row_numbers, Cell, CharClasses, PartNumber},
+};
+
+// A row of the schematic along with the rows around it. `first_row` is the row of the schematic
+// the window starts at and `centre` the index of the row it is centred on within the window.
+#[derive(Debug, Clone)]
+pub struct Window {
+    pub cells: Grid<Cell>,
+    pub first_row: usize,
+    pub centre: usize,
+}
+
+impl Window {
+    // Iterates over the numbers of a row of the window, positioned within the window but
+    // reported by their row in the schematic when too wide
+    pub fn numbers(
+        &self,
+        index: usize,
+    ) -> impl Iterator<Item = miette::Result<PartNumber, AocError>> + '_ {
+        row_numbers(self.first_row + index, self.cells.row(index).unwrap_or(&[])).map(
+            move |number| {
+                number.map(|number| PartNumber {
+                    row: index,
+                    ..number
+                })
+            },
+        )
+    }
+}
+
+fn window_at(
+    rows: &VecDeque<Vec<Cell>>,
+    first_row: usize,
+    centre: usize,
+) -> miette::Result<Window, AocError> {
+    Ok(Window {
+        cells: Grid::from_rows(rows.iter().cloned().collect())?,
+        first_row,
+        centre,
+    })
+}
+
+// Reads a schematic row by row and visits every row along with the ones above and below it,
+// so that only three rows are kept in memory. The blank lines before and after the schematic are
+// left out, like `parse_schematic_with` does, so blank lines are held back until a row follows
+// them.
+pub fn for_each_window<R, V>(
+    mut reader: R,
+    classes: &CharClasses,
+    mut visit: V,
+) -> miette::Result<(), AocError>
+where
+    R: BufRead,
+    V: FnMut(&Window) -> miette::Result<(), AocError>,
+{
+    let mut line = String::new();
+    let mut rows = VecDeque::with_capacity(4);
+    let mut row_count = 0;
+    let mut width = None;
+    let mut pending_blanks = vec![];
+
+    while reader.read_line(&mut line)? != 0 {
+        let cells = classes.classify_line(&line);
+
+        if line.trim().is_empty() {
+            if width.is_some() {
+                pending_blanks.push(cells);
+            }
+        } else {
+            // Blank lines followed by a row are rows too
+            for cells in pending_blanks.drain(..).chain([cells]) {
+                let expected = *width.get_or_insert(cells.len());
+                if cells.len() != expected {
+                    return Err(AocError::RaggedGrid {
+                        row: row_count + 1,
+                        expected,
+                        found: cells.len(),
+                    });
+                }
+
+                rows.push_back(cells);
+                row_count += 1;
+                if rows.len() > 3 {
+                    rows.pop_front();
+                }
+                // The row before the last one read has both its neighbours
+                if row_count >= 2 {
+                    visit(&window_at(&rows, row_count - rows.len(), rows.len() - 2)?)?;
+                }
+            }
+        }
+
+        line.clear();
+    }
+
+    // The last row has no row below it
+    if rows.len() == 3 {
+        rows.pop_front();
+    }
+    if !rows.is_empty() {
+        visit(&window_at(&rows, row_count - rows.len(), rows.len() - 1)?)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_each_window() -> miette::Result<()> {
+        let input = "\n1..\n.2.\n..3\n4..\n\n";
+        let mut windows = vec![];
+        for_each_window(input.as_bytes(), &CharClasses::default(), |window| {
+            let numbers = (0..window.cells.height())
+                .flat_map(|index| window.numbers(index))
+                .map(|number| number.map(|number| number.value))
+                .collect::<miette::Result<Vec<u64>, AocError>>()?;
+            windows.push((window.first_row, window.centre, numbers));
+            Ok(())
+        })?;
+
+        assert_eq!(
+            vec![
+                (0, 0, vec![1, 2]),
+                (0, 1, vec![1, 2, 3]),
+                (1, 1, vec![2, 3, 4]),
+                (2, 1, vec![3, 4])
+            ],
+            windows
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_short_schematics() -> miette::Result<()> {
+        let mut centres = vec![];
+        for_each_window("12*\r\n".as_bytes(), &CharClasses::default(), |window| {
+            centres.push((window.first_row + window.centre, window.cells.height()));
+            Ok(())
+        })?;
+        assert_eq!(vec![(0, 1)], centres);
+
+        let mut visits = 0;
+        for_each_window("\n\n".as_bytes(), &CharClasses::default(), |_| {
+            visits += 1;
+            Ok(())
+        })?;
+        assert_eq!(0, visits);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ragged_rows() {
+        let visit = |_: &Window| Ok(());
+        assert!(matches!(
+            for_each_window("...\n..\n".as_bytes(), &CharClasses::default(), visit),
+            Err(AocError::RaggedGrid {
+                row: 2,
+                expected: 3,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            for_each_window("...\n\n...\n".as_bytes(), &CharClasses::default(), visit),
+            Err(AocError::RaggedGrid {
+                row: 2,
+                expected: 3,
+                found: 0
+            })
+        ));
+    }
+}
@@ -2,12 +2,10 @@ use crate::custom_error::AocError;
 
 fn get_list_pairs(string: Vec<&str>) -> Vec<Vec<u32>> {
     let list1 = string[0]
-        .trim()
         .split_whitespace()
         .map(|elt| elt.parse::<u32>().expect("Should be a number"))
         .collect();
     let list2 = string[1]
-        .trim()
         .split_whitespace()
         .map(|elt| elt.parse::<u32>().expect("Should be a number"))
         .collect();
@@ -24,7 +22,7 @@ pub fn process(input: &str) -> miette::Result<String, AocError> {
         .map(|string| get_list_pairs(string))
         .map(|pair| pair[1].iter().filter(|x| pair[0].contains(x)).count() as u32)
         .fold(0, |acc: u32, x: u32| {
-            acc + if x > 0 { (2 as u32).pow(x - 1) } else { 0 }
+            acc + if x > 0 { 2_u32.pow(x - 1) } else { 0 }
         });
 
     Ok(res.to_string())
@@ -2,12 +2,10 @@ use crate::custom_error::AocError;
 
 fn get_list_pairs(string: Vec<&str>) -> Vec<Vec<u32>> {
     let list1 = string[0]
-        .trim()
         .split_whitespace()
         .map(|elt| elt.parse::<u32>().expect("Should be a number"))
         .collect();
     let list2 = string[1]
-        .trim()
         .split_whitespace()
         .map(|elt| elt.parse::<u32>().expect("Should be a number"))
         .collect();
@@ -25,7 +23,7 @@ pub fn process(input: &str) -> miette::Result<String, AocError> {
         .map(|pair| pair[1].iter().filter(|x| pair[0].contains(x)).count() as u32)
         .inspect(|x| println!("matches : {x}"))
         .fold(0, |acc: u32, x: u32| {
-            let n = acc + if x > 0 { (2 as u32).pow(x - 1) } else { 0 };
+            let n = acc + if x > 0 { 2_u32.pow(x - 1) } else { 0 };
             println!("Score : {n}");
             n
         });